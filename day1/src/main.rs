@@ -14,7 +14,6 @@ impl FromStr for Input {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let snacks = s
             .split("\n\n")
-            .into_iter()
             .map(|elf| {
                 elf.lines()
                     .map(|snack| snack.parse::<u64>())
@@ -0,0 +1,267 @@
+use std::collections::BTreeSet;
+
+use crate::{Instruction, Registers};
+
+/// One clock cycle as the CPU ran it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tick {
+    /// Which cycle this was, counting from 1
+    pub cycle: usize,
+    /// The instruction being worked on
+    pub instruction: Instruction,
+    /// The registers during the cycle. The puzzle samples X here.
+    pub during: Registers,
+    /// The registers once the cycle is over, after any instruction finishing in it has taken
+    /// effect
+    pub after: Registers,
+}
+
+type Hook<'a> = Box<dyn FnMut(&Tick) + 'a>;
+
+/// Runs a program one clock cycle at a time. Each instruction takes as many cycles as
+/// `OPCODES` gives it and only takes effect at the end of its last one, jumps included.
+/// Registers are 32 bit two's complement and arithmetic wraps on overflow, as it would in
+/// hardware, so no program can fault.
+///
+/// Also an iterator over the cycles it runs, ending when the program does.
+pub struct Cpu<'a> {
+    program: Vec<Instruction>,
+    /// Index of the instruction being worked on
+    pc: usize,
+    /// Cycles already spent on the current instruction
+    elapsed: u8,
+    cycle: usize,
+    registers: Registers,
+    /// Called after every cycle, or only after the cycles given
+    hooks: Vec<(Option<BTreeSet<usize>>, Hook<'a>)>,
+}
+
+impl<'a> Cpu<'a> {
+    pub fn new(program: Vec<Instruction>) -> Self {
+        Self {
+            program,
+            pc: 0,
+            elapsed: 0,
+            cycle: 0,
+            registers: Registers::default(),
+            hooks: vec![],
+        }
+    }
+
+    /// Call `hook` after each of the given cycles
+    pub fn on_cycles(
+        mut self,
+        cycles: impl IntoIterator<Item = usize>,
+        hook: impl FnMut(&Tick) + 'a,
+    ) -> Self {
+        self.hooks
+            .push((Some(cycles.into_iter().collect()), Box::new(hook)));
+        self
+    }
+
+    /// Call `hook` after every cycle
+    pub fn on_every_cycle(mut self, hook: impl FnMut(&Tick) + 'a) -> Self {
+        self.hooks.push((None, Box::new(hook)));
+        self
+    }
+
+    /// How many cycles have finished
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    /// The registers as they stand between cycles: after the last one and during the next
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn x(&self) -> i32 {
+        self.registers.x()
+    }
+
+    /// Whether the program has run to its end
+    pub fn is_halted(&self) -> bool {
+        self.pc >= self.program.len()
+    }
+
+    /// Run a single cycle, or nothing if the program has ended
+    pub fn tick(&mut self) -> Option<Tick> {
+        let instruction = *self.program.get(self.pc)?;
+        let during = self.registers;
+        self.cycle += 1;
+        self.elapsed += 1;
+        if self.elapsed >= instruction.cycle_cost() {
+            self.pc = self.execute(instruction);
+            self.elapsed = 0;
+        }
+        let tick = Tick {
+            cycle: self.cycle,
+            instruction,
+            during,
+            after: self.registers,
+        };
+        for (cycles, hook) in &mut self.hooks {
+            if cycles
+                .as_ref()
+                .is_none_or(|cycles| cycles.contains(&tick.cycle))
+            {
+                hook(&tick);
+            }
+        }
+        Some(tick)
+    }
+
+    /// Carry out an instruction, returning the index of the one to run next
+    fn execute(&mut self, instruction: Instruction) -> usize {
+        let registers = &mut self.registers;
+        let next = self.pc + 1;
+        match instruction {
+            Instruction::NoOp => next,
+            Instruction::Add(register, rhs) => {
+                registers[register] = registers[register].wrapping_add(rhs.value(registers));
+                next
+            }
+            Instruction::Sub(register, rhs) => {
+                registers[register] = registers[register].wrapping_sub(rhs.value(registers));
+                next
+            }
+            Instruction::Mul(register, rhs) => {
+                registers[register] = registers[register].wrapping_mul(rhs.value(registers));
+                next
+            }
+            Instruction::Jump(target) => target,
+            Instruction::JumpIfZero(register, target) if registers[register] == 0 => target,
+            Instruction::JumpIfNotZero(register, target) if registers[register] != 0 => target,
+            Instruction::JumpIfZero(..) | Instruction::JumpIfNotZero(..) => next,
+        }
+    }
+
+    /// Run until a cycle satisfies `condition`, returning that cycle, or `None` if the program
+    /// ends first
+    pub fn run_until(&mut self, mut condition: impl FnMut(&Tick) -> bool) -> Option<Tick> {
+        self.find(|tick| condition(tick))
+    }
+
+    /// Run up to and including cycle `cycle`, returning it. `None` if the program ends first,
+    /// or if that cycle has already been run.
+    pub fn run_to_cycle(&mut self, cycle: usize) -> Option<Tick> {
+        if cycle <= self.cycle {
+            return None;
+        }
+        self.run_until(|tick| tick.cycle == cycle)
+    }
+
+    /// Run the program to its end, returning the last cycle run. A program that loops forever
+    /// never returns; bound it with `run_until` or `run_to_cycle` instead.
+    pub fn run(&mut self) -> Option<Tick> {
+        self.last()
+    }
+}
+
+impl Iterator for Cpu<'_> {
+    type Item = Tick;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tick()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser::assemble, Register};
+    use Instruction::*;
+
+    #[test]
+    fn small_program() {
+        let mut cpu = Cpu::new(vec![NoOp, Instruction::addx(3), Instruction::addx(-5)]);
+        let ticks = cpu
+            .by_ref()
+            .map(|tick| (tick.cycle, tick.during.x(), tick.after.x()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ticks,
+            vec![(1, 1, 1), (2, 1, 1), (3, 1, 4), (4, 4, 4), (5, 4, -1)]
+        );
+        assert!(cpu.is_halted());
+        assert_eq!(cpu.x(), -1);
+        assert_eq!(cpu.tick(), None);
+    }
+
+    #[test]
+    fn hooks_and_conditions() {
+        let mut sampled = vec![];
+        let mut cycles = 0;
+        let mut cpu = Cpu::new(vec![
+            Instruction::addx(2),
+            Instruction::addx(3),
+            NoOp,
+            Instruction::addx(-1),
+        ])
+        .on_cycles([2, 4], |tick| sampled.push(tick.during.x()))
+        .on_every_cycle(|_| cycles += 1);
+        let tick = cpu.run_until(|tick| tick.after.x() >= 6).unwrap();
+        assert_eq!((tick.cycle, tick.instruction), (4, Instruction::addx(3)));
+        assert_eq!(cpu.run_to_cycle(3), None);
+        assert_eq!(cpu.run_to_cycle(5).map(|tick| tick.during.x()), Some(6));
+        assert_eq!(cpu.run().map(|tick| tick.cycle), Some(7));
+        drop(cpu);
+        assert_eq!(sampled, vec![1, 3]);
+        assert_eq!(cycles, 7);
+    }
+
+    #[test]
+    fn loops() {
+        // x = 1 * 2^3, counting y down to 0
+        let program = assemble(
+            "addy 3
+loop:
+mulx 2
+suby 1
+jnz y loop",
+        )
+        .unwrap();
+        let mut cpu = Cpu::new(program);
+        let last = cpu.run().unwrap();
+        assert_eq!(cpu.x(), 8);
+        assert_eq!(cpu.registers()[Register::Y], 0);
+        // addy, then three rounds of mulx, suby and jnz
+        assert_eq!(last.cycle, 2 + 3 * (3 + 2 + 2));
+
+        let mut forever = Cpu::new(
+            assemble(
+                "top:
+addz x
+jmp top",
+            )
+            .unwrap(),
+        );
+        let tick = forever
+            .run_until(|tick| tick.after[Register::Z] >= 10)
+            .unwrap();
+        assert_eq!(tick.cycle, 3 * 10 - 1);
+    }
+
+    #[test]
+    fn overflow_wraps() {
+        // squaring x five times takes 3 to 3^32, far past i32
+        let program = assemble(
+            "addx 2
+addy 5
+loop:
+mulx x
+suby 1
+jnz y loop
+addz -2147483648
+subz 1
+addw 2147483647
+addw 1",
+        )
+        .unwrap();
+        let mut cpu = Cpu::new(program);
+        cpu.run();
+        assert_eq!(cpu.x(), 3i32.wrapping_pow(32));
+        assert_eq!(cpu.registers()[Register::Z], i32::MAX);
+        assert_eq!(cpu.registers()[Register::W], i32::MIN);
+    }
+}
@@ -1,136 +1,203 @@
+pub mod cpu;
 pub mod parser;
-use std::ops::{Add, AddAssign};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Instruction {
-    NoOp,
-    AddX(i32),
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+/// One of the CPU's registers. Instructions name them by letter, as in `addx`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Register {
+    X,
+    Y,
+    Z,
+    W,
 }
 
-impl Instruction {
-    pub fn cycle_cost(&self) -> u8 {
+impl Register {
+    pub const ALL: [Self; 4] = [Self::X, Self::Y, Self::Z, Self::W];
+
+    pub fn name(self) -> char {
         match self {
-            Self::NoOp => 1,
-            Self::AddX(_) => 2,
+            Self::X => 'x',
+            Self::Y => 'y',
+            Self::Z => 'z',
+            Self::W => 'w',
         }
     }
 }
 
-pub struct Instructions {
-    instructions: Vec<Instruction>,
-    cursor: usize,
-    delayed_instruction: (usize, Option<Instruction>),
+/// The value of every register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Registers([i32; 4]);
+
+impl Registers {
+    /// X, the register the puzzle is about
+    pub fn x(&self) -> i32 {
+        self[Register::X]
+    }
 }
 
-impl From<Vec<Instruction>> for Instructions {
-    fn from(instructions: Vec<Instruction>) -> Self {
-        Self {
-            instructions,
-            cursor: 0,
-            delayed_instruction: (0, None),
-        }
+/// As the CPU is switched on: X is 1 and everything else is 0
+impl Default for Registers {
+    fn default() -> Self {
+        Self([1, 0, 0, 0])
     }
 }
 
-impl Iterator for Instructions {
-    type Item = Instruction;
+impl Index<Register> for Registers {
+    type Output = i32;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.delayed_instruction {
-            (0, None) => { /* do nothing, go on! */ }
-            (0, Some(instruction)) => {
-                self.delayed_instruction = (0, None);
-                return Some(instruction);
-            }
-            (delay, opt_instruction) => {
-                self.delayed_instruction = (delay - 1, opt_instruction);
-                return Some(Instruction::NoOp);
-            }
-        }
-        match self.instructions.get(self.cursor)? {
-            Instruction::NoOp => {
-                self.cursor += 1;
-                Some(Instruction::NoOp)
-            }
-            instruction @ Instruction::AddX(_) => {
-                self.cursor += 1;
-                self.delayed_instruction = (0, Some(*instruction));
-                Some(Instruction::NoOp)
-            }
-        }
+    fn index(&self, register: Register) -> &Self::Output {
+        &self.0[register as usize]
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Register(pub i32);
-
-impl Default for Register {
-    fn default() -> Self {
-        Self(1)
+impl IndexMut<Register> for Registers {
+    fn index_mut(&mut self, register: Register) -> &mut Self::Output {
+        &mut self.0[register as usize]
     }
 }
 
-impl AddAssign<Instruction> for Register {
-    fn add_assign(&mut self, rhs: Instruction) {
-        match rhs {
-            Instruction::NoOp => {}
-            Instruction::AddX(rhs) => self.0 += rhs,
+/// The right hand side of an arithmetic instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Value(i32),
+    Register(Register),
+}
+
+impl Operand {
+    pub fn value(self, registers: &Registers) -> i32 {
+        match self {
+            Self::Value(n) => n,
+            Self::Register(register) => registers[register],
         }
     }
 }
-impl Add<Instruction> for Register {
-    type Output = Self;
 
-    fn add(self, rhs: Instruction) -> Self::Output {
-        match rhs {
-            Instruction::NoOp => self,
-            Instruction::AddX(n) => Self(self.0 + n),
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeInfo {
+    pub opcode: Opcode,
+    /// How the instruction is written. Arithmetic mnemonics are followed by the register they
+    /// write to, as in `addx`.
+    pub mnemonic: &'static str,
+    /// How many cycles the instruction takes. It takes effect at the end of the last one.
+    pub cycles: u8,
+}
+
+/// Define `Opcode` and `OPCODES` from one list, so the table is always in the enum's order
+macro_rules! opcodes {
+    ($($opcode:ident => $mnemonic:literal in $cycles:literal,)*) => {
+        /// What kind of instruction something is, without its arguments
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Opcode {
+            $($opcode,)*
         }
+
+        /// Every opcode in the instruction set, in the order of `Opcode`. This is the one place
+        /// cycle costs are defined: the CPU, the assembler and any other tooling all look them
+        /// up here.
+        pub const OPCODES: &[OpcodeInfo] = &[$(OpcodeInfo {
+            opcode: Opcode::$opcode,
+            mnemonic: $mnemonic,
+            cycles: $cycles,
+        },)*];
+    };
+}
+
+opcodes! {
+    NoOp => "noop" in 1,
+    Add => "add" in 2,
+    Sub => "sub" in 2,
+    Mul => "mul" in 3,
+    Jump => "jmp" in 1,
+    JumpIfZero => "jz" in 2,
+    JumpIfNotZero => "jnz" in 2,
+}
+
+impl Opcode {
+    pub fn info(self) -> &'static OpcodeInfo {
+        &OPCODES[self as usize]
     }
 }
 
-/// From a list of instructions, return the portion that happen in `cycle` cycles or less
-pub fn get_instructions_up_to_clock_cycle(
-    cycle: usize,
-    instructions: &[Instruction],
-) -> impl Iterator<Item = &Instruction> {
-    instructions.iter().scan(0, move |state, instruction| {
-        *state += instruction.cycle_cost() as usize;
-        if *state < cycle {
-            Some(instruction)
-        } else {
-            None
-        }
-    })
+/// An instruction, with any jump target resolved to the index of the instruction it lands on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    NoOp,
+    Add(Register, Operand),
+    Sub(Register, Operand),
+    Mul(Register, Operand),
+    Jump(usize),
+    JumpIfZero(Register, usize),
+    JumpIfNotZero(Register, usize),
 }
 
-impl Register {
-    pub fn reduce_instructions(self, instructions: impl Iterator<Item = Instruction>) -> Self {
-        instructions.fold(self, |register, instruction| register + instruction)
+impl Instruction {
+    /// The puzzle's `addx`
+    pub fn addx(n: i32) -> Self {
+        Self::Add(Register::X, Operand::Value(n))
     }
-    pub fn iter_with_instructions(&self, instructions: Vec<Instruction>) -> Vec<Register> {
-        let mut instructions = instructions.into_iter();
-        let mut cycle = 1;
-        let mut register = Register::default();
-        let mut registers = vec![register.clone()];
-        while cycle <= 240 {
-            match instructions.next() {
-                Some(Instruction::NoOp) => {
-                    registers.push(register.clone());
-                    cycle += 1;
-                }
-                Some(instruction @ Instruction::AddX(_)) => {
-                    registers.push(register.clone());
-                    cycle += 1;
-                    register += instruction;
-                    registers.push(register.clone());
-                    cycle += 1;
-                }
-                None => {
-                    panic!("We ran out of instructions before we ran out of cycles");
-                }
+
+    pub fn opcode(&self) -> Opcode {
+        match self {
+            Self::NoOp => Opcode::NoOp,
+            Self::Add(..) => Opcode::Add,
+            Self::Sub(..) => Opcode::Sub,
+            Self::Mul(..) => Opcode::Mul,
+            Self::Jump(_) => Opcode::Jump,
+            Self::JumpIfZero(..) => Opcode::JumpIfZero,
+            Self::JumpIfNotZero(..) => Opcode::JumpIfNotZero,
+        }
+    }
+
+    pub fn cycle_cost(&self) -> u8 {
+        self.opcode().info().cycles
+    }
+}
+
+/// Disassembles the instruction. Jump targets have no labels left, so they show as instruction
+/// indices, such as `jnz x @3`.
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mnemonic = self.opcode().info().mnemonic;
+        let operand = |operand: &Operand| match operand {
+            Operand::Value(n) => n.to_string(),
+            Operand::Register(register) => register.name().to_string(),
+        };
+        match self {
+            Self::NoOp => write!(f, "{mnemonic}"),
+            Self::Add(register, rhs) | Self::Sub(register, rhs) | Self::Mul(register, rhs) => {
+                write!(f, "{mnemonic}{} {}", register.name(), operand(rhs))
             }
+            Self::Jump(target) => write!(f, "{mnemonic} @{target}"),
+            Self::JumpIfZero(register, target) | Self::JumpIfNotZero(register, target) => {
+                write!(f, "{mnemonic} {} @{target}", register.name())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opcode_table() {
+        for info in OPCODES {
+            assert_eq!(info.opcode.info(), info);
         }
-        todo!()
+        assert_eq!(Instruction::NoOp.cycle_cost(), 1);
+        assert_eq!(Instruction::addx(3).cycle_cost(), 2);
+        assert_eq!(Instruction::addx(-3).to_string(), "addx -3");
+        assert_eq!(
+            Instruction::Mul(Register::Y, Operand::Register(Register::X)).to_string(),
+            "muly x"
+        );
+        assert_eq!(
+            Instruction::JumpIfNotZero(Register::Z, 3).to_string(),
+            "jnz z @3"
+        );
     }
 }
@@ -1,8 +1,12 @@
 use std::{convert::Infallible, str::FromStr};
 
-use day10::{get_instructions_up_to_clock_cycle, parser, Instruction, Instructions, Register};
+use day10::{cpu::Cpu, parser, Instruction};
 
 const INPUT: &str = include_str!("input.txt");
+/// The cycles whose signal strength part 1 adds up
+const SIGNAL_CYCLES: [usize; 6] = [20, 60, 100, 140, 180, 220];
+const SCREEN_WIDTH: usize = 40;
+const SCREEN_HEIGHT: usize = 6;
 
 #[derive(Debug, Clone)]
 struct Input(String);
@@ -15,54 +19,39 @@ impl FromStr for Input {
 }
 impl Input {
     fn into_instructions(self) -> Result<Vec<Instruction>, String> {
-        let (_, instructions) =
-            parser::parse(&self.0).map_err(|_| "Failed to parse instructions".to_string())?;
-        Ok(instructions)
+        parser::assemble(&self.0).map_err(|e| e.to_string())
     }
 }
 
 fn solve_part1(input: Input) -> i32 {
-    let register = Register::default();
     let instructions = input
         .into_instructions()
         .expect("input must parse into instructions");
-    let registers_at_cycles = [20, 60, 100, 140, 180, 220]
-        .map(|cycle| {
-            (
-                cycle,
-                get_instructions_up_to_clock_cycle(cycle, &instructions),
-            )
+    let mut strength = 0;
+    Cpu::new(instructions)
+        .on_cycles(SIGNAL_CYCLES, |tick| {
+            strength += tick.cycle as i32 * tick.during.x()
         })
-        .map(move |(cycle, instructions)| {
-            (
-                cycle,
-                register.clone().reduce_instructions(instructions.copied()),
-            )
-        })
-        .into_iter();
-
-    registers_at_cycles.fold(0, |acc, (cycle, Register(n))| acc + n * (cycle as i32))
+        .run();
+    strength
 }
 
 fn solve_part2(input: Input) -> String {
-    let register = Register::default();
-    let instructions: Instructions = input
+    let instructions = input
         .into_instructions()
-        .expect("input must parse into instructions")
-        .into();
-    instructions
-        .scan(register, |register, instruction| {
-            let result = register.clone();
-            *register += instruction;
-            Some(result)
+        .expect("input must parse into instructions");
+    Cpu::new(instructions)
+        .take(SCREEN_WIDTH * SCREEN_HEIGHT)
+        .map(|tick| {
+            let pixel = ((tick.cycle - 1) % SCREEN_WIDTH) as i32;
+            let x = tick.during.x();
+            (x - 1..=x + 1).contains(&pixel)
         })
-        .collect::<Vec<Register>>()
-        .chunks_exact(40)
+        .collect::<Vec<bool>>()
+        .chunks_exact(SCREEN_WIDTH)
         .map(|line| {
             line.iter()
-                .zip(0..)
-                .map(|(Register(x), pixel)| (x - 1..=x + 1).contains(&pixel))
-                .map(|enabled| if enabled { '#' } else { '.' })
+                .map(|&enabled| if enabled { '#' } else { '.' })
                 .collect::<String>()
         })
         .collect::<Vec<String>>()
@@ -104,49 +93,27 @@ mod tests {
 
     #[test]
     fn instructions_by_cycle() {
-        let input: Instructions = INPUT
-            .parse::<Input>()
-            .unwrap()
-            .into_instructions()
-            .unwrap()
-            .into();
-        let instructions = input.collect::<Vec<Instruction>>();
+        let instructions = INPUT.parse::<Input>().unwrap().into_instructions().unwrap();
         assert_eq!(
-            instructions[..10],
+            instructions[..5],
             vec![
-                Instruction::NoOp,
-                Instruction::AddX(15),
-                Instruction::NoOp,
-                Instruction::AddX(-11),
-                Instruction::NoOp,
-                Instruction::AddX(6),
-                Instruction::NoOp,
-                Instruction::AddX(-3),
-                Instruction::NoOp,
-                Instruction::AddX(5)
+                Instruction::addx(15),
+                Instruction::addx(-11),
+                Instruction::addx(6),
+                Instruction::addx(-3),
+                Instruction::addx(5),
             ]
         );
-        let registers = instructions
-            .into_iter()
-            .scan(Register::default(), |register, instruction| {
-                *register += instruction;
-                Some(register.clone())
-            })
-            .collect::<Vec<Register>>();
+        let ticks = Cpu::new(instructions).take(10).collect::<Vec<_>>();
         assert_eq!(
-            registers.chunks_exact(40).next().unwrap()[..10],
-            vec![
-                Register(1),
-                Register(16),
-                Register(16),
-                Register(5),
-                Register(5),
-                Register(11),
-                Register(11),
-                Register(8),
-                Register(8),
-                Register(13),
-            ]
+            ticks.iter().map(|tick| tick.after.x()).collect::<Vec<_>>(),
+            vec![1, 16, 16, 5, 5, 11, 11, 8, 8, 13]
+        );
+        assert_eq!(
+            ticks.iter().map(|tick| tick.during.x()).collect::<Vec<_>>(),
+            vec![1, 1, 16, 16, 5, 5, 11, 11, 8, 8]
         );
+        let mut cpu = Cpu::new(INPUT.parse::<Input>().unwrap().into_instructions().unwrap());
+        assert_eq!(cpu.run_to_cycle(20).map(|tick| tick.during.x()), Some(21));
     }
 }
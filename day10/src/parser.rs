@@ -1,36 +1,212 @@
+use std::collections::HashMap;
+
 use nom::{
     self,
     branch::alt,
-    bytes::complete::tag,
-    character::complete::{self, digit1, newline},
-    combinator::{all_consuming, map_res, opt},
-    multi::separated_list1,
+    bytes::complete::{tag, take_while1},
+    character::complete::{self, one_of, space0, space1},
+    combinator::{all_consuming, map, opt},
+    error::ErrorKind,
+    sequence::{delimited, preceded, separated_pair, terminated},
     IResult,
 };
+use thiserror::Error;
 
-use crate::Instruction;
+use crate::{Instruction, Opcode, Operand, Register, OPCODES};
 
-fn instruction_addx(input: &str) -> IResult<&str, Instruction> {
-    let (input, _) = tag("addx ")(input)?;
-    let (input, is_negative) = opt(complete::char('-'))(input)?;
-    let (input, n) = map_res(digit1, |s: &str| s.parse::<i32>())(input)?;
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AssemblyError {
+    #[error("Line {line} is not an instruction or a label: {text}")]
+    Syntax { line: usize, text: String },
+    #[error("Jump to label {0} which is never defined")]
+    UnknownLabel(String),
+    #[error("Label {0} is defined more than once")]
+    DuplicateLabel(String),
+}
 
-    let result = match is_negative {
-        Some(_) => Instruction::AddX(-n),
-        None => Instruction::AddX(n),
-    };
+/// An instruction whose jump target may still be a label
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unresolved<'a> {
+    Ready(Instruction),
+    Jump(&'a str),
+    JumpIfZero(Register, &'a str),
+    JumpIfNotZero(Register, &'a str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line<'a> {
+    Label(&'a str),
+    Instruction(Unresolved<'a>),
+    Blank,
+}
 
-    Ok((input, result))
+fn register(input: &str) -> IResult<&str, Register> {
+    map(one_of("xyzw"), |name| {
+        Register::ALL
+            .into_iter()
+            .find(|register| register.name() == name)
+            .expect("every register name is listed")
+    })(input)
 }
 
-fn instruction_noop(input: &str) -> IResult<&str, Instruction> {
-    let (input, _) = tag("noop")(input)?;
-    Ok((input, Instruction::NoOp))
+fn operand(input: &str) -> IResult<&str, Operand> {
+    alt((
+        map(register, Operand::Register),
+        map(complete::i32, Operand::Value),
+    ))(input)
 }
-fn instruction(input: &str) -> IResult<&str, Instruction> {
-    alt((instruction_addx, instruction_noop))(input)
+
+fn label(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '_')(input)
 }
 
-pub fn parse(input: &str) -> IResult<&str, Vec<Instruction>> {
-    all_consuming(separated_list1(newline, instruction))(input)
+/// The longest mnemonic in `OPCODES` that starts the input
+fn opcode(input: &str) -> IResult<&str, Opcode> {
+    OPCODES
+        .iter()
+        .filter(|info| input.starts_with(info.mnemonic))
+        .max_by_key(|info| info.mnemonic.len())
+        .map(|info| (&input[info.mnemonic.len()..], info.opcode))
+        .ok_or_else(|| nom::Err::Error(nom::error::Error::new(input, ErrorKind::Tag)))
+}
+
+fn instruction(input: &str) -> IResult<&str, Unresolved<'_>> {
+    let (input, opcode) = opcode(input)?;
+    let arithmetic = |input| separated_pair(register, space1, operand)(input);
+    let conditional = |input| preceded(space1, separated_pair(register, space1, label))(input);
+    match opcode {
+        Opcode::NoOp => Ok((input, Unresolved::Ready(Instruction::NoOp))),
+        Opcode::Add => map(arithmetic, |(register, rhs)| {
+            Unresolved::Ready(Instruction::Add(register, rhs))
+        })(input),
+        Opcode::Sub => map(arithmetic, |(register, rhs)| {
+            Unresolved::Ready(Instruction::Sub(register, rhs))
+        })(input),
+        Opcode::Mul => map(arithmetic, |(register, rhs)| {
+            Unresolved::Ready(Instruction::Mul(register, rhs))
+        })(input),
+        Opcode::Jump => map(preceded(space1, label), Unresolved::Jump)(input),
+        Opcode::JumpIfZero => map(conditional, |(register, target)| {
+            Unresolved::JumpIfZero(register, target)
+        })(input),
+        Opcode::JumpIfNotZero => map(conditional, |(register, target)| {
+            Unresolved::JumpIfNotZero(register, target)
+        })(input),
+    }
+}
+
+/// A label such as `loop:`, an instruction, or nothing, with any `#` comment after it
+fn line(input: &str) -> IResult<&str, Line<'_>> {
+    let comment = opt(preceded(tag("#"), complete::not_line_ending));
+    all_consuming(terminated(
+        delimited(
+            space0,
+            map(
+                opt(alt((
+                    map(terminated(label, tag(":")), Line::Label),
+                    map(instruction, Line::Instruction),
+                ))),
+                |line| line.unwrap_or(Line::Blank),
+            ),
+            space0,
+        ),
+        comment,
+    ))(input)
+}
+
+/// Assemble a program of one instruction or label per line. Labels name the instruction after
+/// them, and jumps to them are resolved to that instruction's index.
+pub fn assemble(input: &str) -> Result<Vec<Instruction>, AssemblyError> {
+    let mut labels = HashMap::new();
+    let mut unresolved = vec![];
+    for (number, text) in (1..).zip(input.lines()) {
+        let (_, line) = line(text).map_err(|_| AssemblyError::Syntax {
+            line: number,
+            text: text.to_string(),
+        })?;
+        match line {
+            Line::Label(name) => {
+                if labels.insert(name, unresolved.len()).is_some() {
+                    return Err(AssemblyError::DuplicateLabel(name.to_string()));
+                }
+            }
+            Line::Instruction(instruction) => unresolved.push(instruction),
+            Line::Blank => {}
+        }
+    }
+
+    let target = |name: &str| {
+        labels
+            .get(name)
+            .copied()
+            .ok_or_else(|| AssemblyError::UnknownLabel(name.to_string()))
+    };
+    unresolved
+        .into_iter()
+        .map(|instruction| {
+            Ok(match instruction {
+                Unresolved::Ready(instruction) => instruction,
+                Unresolved::Jump(name) => Instruction::Jump(target(name)?),
+                Unresolved::JumpIfZero(register, name) => {
+                    Instruction::JumpIfZero(register, target(name)?)
+                }
+                Unresolved::JumpIfNotZero(register, name) => {
+                    Instruction::JumpIfNotZero(register, target(name)?)
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels() {
+        let program = "  addy 3   # count down from 3
+loop:
+mulx 2
+suby 1
+jnz y loop
+jz y end
+noop
+end:";
+        let instructions = assemble(program).expect("program must assemble");
+        assert_eq!(
+            instructions,
+            vec![
+                Instruction::Add(Register::Y, Operand::Value(3)),
+                Instruction::Mul(Register::X, Operand::Value(2)),
+                Instruction::Sub(Register::Y, Operand::Value(1)),
+                Instruction::JumpIfNotZero(Register::Y, 1),
+                Instruction::JumpIfZero(Register::Y, 6),
+                Instruction::NoOp,
+            ]
+        );
+        let disassembled = instructions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(disassembled[3], "jnz y @1");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            assemble("noop\naddq 1"),
+            Err(AssemblyError::Syntax {
+                line: 2,
+                text: "addq 1".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("jmp nowhere"),
+            Err(AssemblyError::UnknownLabel("nowhere".to_string()))
+        );
+        assert_eq!(
+            assemble("a:\na:"),
+            Err(AssemblyError::DuplicateLabel("a".to_string()))
+        );
+    }
 }
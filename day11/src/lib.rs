@@ -49,15 +49,6 @@ impl Rem<i64> for MonkeyNum {
         self.0 % rhs
     }
 }
-#[cfg(test)]
-mod monkeynum_tests {
-
-    use super::MonkeyNum;
-    #[test]
-    fn parse() {
-        "22".parse::<MonkeyNum>().unwrap();
-    }
-}
 impl MonkeyMath for MonkeyNum {
     fn add_constant(&mut self, constant: usize) {
         self.0 += constant as i64;
@@ -186,3 +177,13 @@ impl<T> Monkeys<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod monkeynum_tests {
+
+    use super::MonkeyNum;
+    #[test]
+    fn parse() {
+        "22".parse::<MonkeyNum>().unwrap();
+    }
+}
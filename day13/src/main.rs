@@ -7,7 +7,7 @@ const INPUT: &str = include_str!("input.txt");
 fn solve_part1(s: &str) -> usize {
     let (_, packet_pairs) = parser::packet_pairs::<u8>(s).expect("input must parse");
     (1..)
-        .zip(packet_pairs.into_iter())
+        .zip(packet_pairs)
         .filter_map(|(i, (a, b))| {
             if let Some(Ordering::Less) | Some(Ordering::Equal) = a.partial_cmp(&b) {
                 Some(i)
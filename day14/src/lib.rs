@@ -76,7 +76,7 @@ impl Display for SandMaze {
             f,
             "+{}+",
             ['-']
-                .repeat((max_x as i32 - min_x as i32) as usize + 1)
+                .repeat((max_x - min_x) as usize + 1)
                 .into_iter()
                 .collect::<String>()
         )?;
@@ -142,7 +142,7 @@ impl SandMaze {
         }
     }
     pub fn step(&mut self) -> Option<(i32, i32)> {
-        if self.tiles.get(&self.sand_origin).is_some() {
+        if self.tiles.contains_key(&self.sand_origin) {
             return None;
         }
         let mut sand = self.sand_origin;
@@ -153,7 +153,7 @@ impl SandMaze {
                 .fold(
                     None,
                     |acc: Option<(i32, i32)>, next @ (_, y)| -> Option<(i32, i32)> {
-                        acc.or(if self.tiles.get(&next).is_none() && y < floor {
+                        acc.or(if !self.tiles.contains_key(&next) && y < floor {
                             Some(next)
                         } else {
                             None
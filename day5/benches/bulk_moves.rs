@@ -0,0 +1,88 @@
+//! Throughput of `Yard::act` on generated yards holding millions of crates. Run with
+//! `cargo bench -p day5`.
+use std::time::{Duration, Instant};
+
+use day5::{stack::RopeStack, stack::Stack, Crate, Instruction, MoveKind, Yard};
+
+const STACKS: usize = 9;
+const CRATES_PER_STACK: usize = 1_000_000;
+const MOVES: usize = 2_000;
+
+/// A small xorshift generator, so runs are repeatable without pulling in a dependency
+struct Rng(u64);
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+fn yard<S: Stack>() -> Yard<S> {
+    Yard::new(
+        (0..STACKS)
+            .map(|stack| {
+                (0..CRATES_PER_STACK)
+                    .map(|i| Crate::new((b'A' + ((stack + i) % 26) as u8) as char))
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+/// Moves of up to half of a stack at a time, always between stacks that can afford them
+fn instructions(kind: MoveKind) -> Vec<Instruction> {
+    let mut rng = Rng(0x2022_1205);
+    let mut heights = [CRATES_PER_STACK; STACKS];
+    (0..MOVES)
+        .map(|_| {
+            let src = rng.below(STACKS);
+            let dst = (src + 1 + rng.below(STACKS - 1)) % STACKS;
+            let count = rng.below(heights[src] / 2 + 1);
+            heights[src] -= count;
+            heights[dst] += count;
+            Instruction::new(count, src, dst, kind)
+        })
+        .collect()
+}
+
+fn run<S: Stack>(instructions: &[Instruction]) -> Duration {
+    let mut yard: Yard<S> = yard();
+    let start = Instant::now();
+    for instruction in instructions {
+        yard.act(instruction.clone())
+            .expect("generated moves must be valid");
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let kinds = [
+        MoveKind::Individual,
+        MoveKind::Grouped,
+        MoveKind::Chunked(1000),
+        MoveKind::Rotated,
+    ];
+    println!(
+        "{} stacks of {} crates, {} moves each",
+        STACKS, CRATES_PER_STACK, MOVES
+    );
+    for kind in kinds {
+        let instructions = instructions(kind);
+        let kind = format!("{kind:?}");
+        let crates: usize = instructions.iter().map(Instruction::count).sum();
+        for (name, elapsed) in [
+            ("Vec", run::<Vec<Crate>>(&instructions)),
+            ("RopeStack", run::<RopeStack>(&instructions)),
+        ] {
+            let throughput = crates as f64 / elapsed.as_secs_f64() / 1e6;
+            println!(
+                "{kind:<14} {name:<10} {crates:>12} crates in {elapsed:>10.2?} ({throughput:>10.1} M crates/s)"
+            );
+        }
+    }
+}
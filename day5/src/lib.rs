@@ -1,5 +1,9 @@
 pub mod parser;
+pub mod stack;
 
+use std::{collections::HashSet, fmt::Display};
+
+use stack::Stack;
 use thiserror::Error;
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -10,6 +14,12 @@ impl Crate {
     }
 }
 
+impl Display for Crate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.0)
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Instruction {
     count: usize,
@@ -19,6 +29,18 @@ pub struct Instruction {
 }
 
 impl Instruction {
+    /// Move `count` crates from stack `src` to stack `dst`, both zero-indexed
+    pub fn new(count: usize, src: usize, dst: usize, kind: MoveKind) -> Self {
+        Self {
+            count,
+            src,
+            dst,
+            kind,
+        }
+    }
+    pub fn count(&self) -> usize {
+        self.count
+    }
     pub fn to_individual(self) -> Self {
         Self {
             kind: MoveKind::Individual,
@@ -31,10 +53,14 @@ impl Instruction {
             ..self
         }
     }
+    pub fn with_kind(self, kind: MoveKind) -> Self {
+        Self { kind, ..self }
+    }
+    /// Swaps between the CrateMover 9000 and 9001. Any other crane model toggles to Individual.
     pub fn toggle_kind(self) -> Self {
         let new_kind = match self.kind {
             MoveKind::Individual => MoveKind::Grouped,
-            MoveKind::Grouped => MoveKind::Individual,
+            _ => MoveKind::Individual,
         };
         Self {
             kind: new_kind,
@@ -45,8 +71,43 @@ impl Instruction {
 
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
 pub enum MoveKind {
+    /// CrateMover 9000: moves crates one at a time
     Individual,
+    /// CrateMover 9001: moves all crates at once, keeping their order
     Grouped,
+    /// Moves crates in lifts of at most this many, each lift keeping its order. A capacity of 0
+    /// means no limit: everything goes in a single lift, as with `Grouped`.
+    Chunked(usize),
+    /// Moves all crates at once, but spins the group so its topmost crate lands at the bottom
+    Rotated,
+}
+
+/// A crane model: how it moves crates, and which stacks (zero-indexed) it can reach. A crane
+/// without a reach restriction can reach every stack.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Crane {
+    kind: MoveKind,
+    reach: Option<HashSet<usize>>,
+}
+
+impl Crane {
+    pub fn new(kind: MoveKind) -> Self {
+        Self { kind, reach: None }
+    }
+    pub fn with_reach(self, stacks: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            reach: Some(stacks.into_iter().collect()),
+            ..self
+        }
+    }
+    pub fn kind(&self) -> MoveKind {
+        self.kind
+    }
+    pub fn can_reach(&self, stack: usize) -> bool {
+        self.reach
+            .as_ref()
+            .is_none_or(|reach| reach.contains(&stack))
+    }
 }
 
 #[derive(Debug, Error)]
@@ -57,13 +118,35 @@ pub enum InstructionError {
     DestinationNotFound(usize),
     #[error("Tried to move {0} items, but only {1} exist")]
     InsufficientCountError(usize, usize),
-    #[error("Tried to move more items than exist")]
-    PopError,
+    #[error("Crane cannot reach stack {0}")]
+    OutOfReach(usize),
+    #[error("Tried to move items from stack {0} onto itself")]
+    SameStack(usize),
+}
+
+/// The result of checking a list of instructions against a yard without moving any crates
+#[derive(Debug)]
+pub struct DryRun {
+    /// Every instruction that would fail, by its index in the list. Failing instructions are
+    /// skipped, so later ones are checked as if the failing ones had never been given.
+    pub failures: Vec<(usize, InstructionError)>,
+    /// For each step, the minimum height each stack needs before that step for it and every
+    /// following instruction to have enough crates to move. Like the checks, these skip the
+    /// failing instructions, so they describe the program as it would actually run.
+    pub required_heights: Vec<Vec<usize>>,
+}
+
+impl DryRun {
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct Yard(Vec<Vec<Crate>>);
-impl From<Vec<(usize, Crate)>> for Yard {
+/// The stacks of crates, each stored as `S`. Plain vectors are the default; see
+/// `stack::RopeStack` for moving very large groups.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Yard<S = Vec<Crate>>(Vec<S>);
+impl<S: Stack> From<Vec<(usize, Crate)>> for Yard<S> {
     fn from(input: Vec<(usize, Crate)>) -> Self {
         let mut result: Vec<Vec<Crate>> = Vec::new();
         for (slot, crate_) in input.into_iter() {
@@ -78,52 +161,176 @@ impl From<Vec<(usize, Crate)>> for Yard {
         }
         // since we construct these vectors from top down, we need to reverse them when finished so the
         // top is back at the top.
-        for sub_vec in result.iter_mut() {
-            sub_vec.reverse()
+        Self(
+            result
+                .into_iter()
+                .map(|sub_vec| sub_vec.into_iter().rev().collect())
+                .collect(),
+        )
+    }
+}
+
+/// Renders the yard as the crate-stack diagram it was parsed from: one row per level with
+/// `[X]` or blank 3-char cells, followed by the numbered base row. Padding is kept so that
+/// the output can be fed straight back into `parser::parse`.
+impl<S: Stack> Display for Yard<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let height = self.0.iter().map(S::len).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let row = self
+                .0
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(crate_) => crate_.to_string(),
+                    None => "   ".to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(f, "{row}")?;
         }
-        Self(result)
+        let base = (1..=self.0.len())
+            .map(|n| format!("{n:^3}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(f, "{base}")
     }
 }
 
-impl Yard {
-    pub fn new(data: Vec<Vec<Crate>>) -> Self {
+impl<S: Stack> Yard<S> {
+    pub fn new(data: Vec<S>) -> Self {
         Self(data)
     }
+    /// Build a yard of at least `stack_count` stacks from (slot, crate) pairs listed top down,
+    /// so that empty stacks at the end of the diagram are kept.
+    pub fn from_diagram(stack_count: usize, crates: Vec<(usize, Crate)>) -> Self {
+        let mut yard: Self = crates.into();
+        yard.0
+            .resize_with(stack_count.max(yard.0.len()), S::default);
+        yard
+    }
+    /// Move the crates out of one stack and into another as whole groups, rather than crate by
+    /// crate, so the cost depends on the stack representation rather than on the count.
     pub fn act(&mut self, instruction: Instruction) -> Result<(), InstructionError> {
-        let src = self
+        let Instruction {
+            count,
+            src,
+            dst,
+            kind,
+        } = instruction;
+        let available = self
             .0
-            .get_mut(instruction.src)
-            .ok_or(InstructionError::SourceNotFound(instruction.src))?;
-        if src.len() < instruction.count {
-            return Err(InstructionError::InsufficientCountError(
-                instruction.count,
-                src.len(),
-            ));
+            .get(src)
+            .ok_or(InstructionError::SourceNotFound(src))?
+            .len();
+        if dst >= self.0.len() {
+            return Err(InstructionError::DestinationNotFound(dst));
+        }
+        if available < count {
+            return Err(InstructionError::InsufficientCountError(count, available));
+        }
+        let mut lifted = self.0[src].split_top(count);
+        let dst = &mut self.0[dst];
+        match kind {
+            // moving crates one by one puts the group down upside down
+            MoveKind::Individual => {
+                lifted.reverse();
+                dst.append(lifted);
+            }
+            MoveKind::Grouped => dst.append(lifted),
+            MoveKind::Chunked(capacity) => {
+                let capacity = if capacity == 0 { count } else { capacity };
+                while !lifted.is_empty() {
+                    let lift = lifted.split_top(capacity.min(lifted.len()));
+                    dst.append(lift);
+                }
+            }
+            MoveKind::Rotated => {
+                let mut spun = lifted.split_top(count.min(1));
+                spun.append(lifted);
+                dst.append(spun);
+            }
         }
-        let data = (0..instruction.count).map(|_| src.pop().ok_or(InstructionError::PopError));
-        // since we're picking off the stack one by one, if we're supposed to be moving things
-        // as a group we have to reverse the order here
-        let data = match instruction.kind {
-            MoveKind::Individual => data.collect::<Result<Vec<_>, _>>()?,
-            MoveKind::Grouped => data
-                .collect::<Result<Vec<_>, _>>()?
-                .into_iter()
-                .rev()
-                .collect(),
-        };
-        let dst = self
-            .0
-            .get_mut(instruction.dst)
-            .ok_or(InstructionError::DestinationNotFound(instruction.dst))?;
-        dst.extend(data);
         Ok(())
     }
 
+    /// Act out an instruction using the given crane, regardless of the instruction's own kind
+    pub fn act_with(
+        &mut self,
+        crane: &Crane,
+        instruction: Instruction,
+    ) -> Result<(), InstructionError> {
+        if let Some(&stack) = [instruction.src, instruction.dst]
+            .iter()
+            .find(|&&stack| !crane.can_reach(stack))
+        {
+            return Err(InstructionError::OutOfReach(stack));
+        }
+        self.act(instruction.with_kind(crane.kind))
+    }
+
+    /// Check every instruction against the stack heights of this yard without moving anything,
+    /// collecting all failures instead of stopping at the first one.
+    pub fn dry_run(&self, instructions: &[Instruction]) -> DryRun {
+        let stack_count = self.0.len();
+        let mut heights: Vec<usize> = self.0.iter().map(S::len).collect();
+        let mut failures = Vec::new();
+        let mut failed = vec![false; instructions.len()];
+        for (idx, instruction) in instructions.iter().enumerate() {
+            let Instruction {
+                count, src, dst, ..
+            } = *instruction;
+            let failure = if src >= stack_count {
+                Some(InstructionError::SourceNotFound(src))
+            } else if dst >= stack_count {
+                Some(InstructionError::DestinationNotFound(dst))
+            } else if src == dst {
+                Some(InstructionError::SameStack(src))
+            } else if heights[src] < count {
+                Some(InstructionError::InsufficientCountError(
+                    count,
+                    heights[src],
+                ))
+            } else {
+                None
+            };
+            match failure {
+                Some(failure) => {
+                    failures.push((idx, failure));
+                    failed[idx] = true;
+                }
+                None => {
+                    heights[src] -= count;
+                    heights[dst] += count;
+                }
+            }
+        }
+
+        // walk backwards from the end, where nothing more is needed, working out how tall each
+        // stack must be before each step
+        let mut required = vec![0usize; stack_count];
+        let mut required_heights = vec![Vec::new(); instructions.len()];
+        for (idx, instruction) in instructions.iter().enumerate().rev() {
+            let Instruction {
+                count, src, dst, ..
+            } = *instruction;
+            if !failed[idx] {
+                required[dst] = required[dst].saturating_sub(count);
+                required[src] += count;
+            }
+            required_heights[idx] = required.clone();
+        }
+
+        DryRun {
+            failures,
+            required_heights,
+        }
+    }
+
     pub fn topmost(&self) -> Option<String> {
         self.0
             .iter()
             .map(|v| {
-                if let Some(Crate(c)) = v.last() {
+                if let Some(Crate(c)) = v.top() {
                     Some(c)
                 } else {
                     None
@@ -165,6 +372,20 @@ mod tests {
         assert_eq!(yard.topmost(), Some("DMZ".to_string()));
     }
 
+    #[test]
+    fn display() {
+        let yard = Yard(vec![
+            vec![Crate('Z'), Crate('N')],
+            vec![Crate('M'), Crate('C'), Crate('D')],
+            vec![Crate('P')],
+        ]);
+        let expected = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 ";
+        assert_eq!(yard.to_string(), expected);
+    }
+
     #[test]
     fn act() {
         let mut yard = Yard(vec![
@@ -187,4 +408,128 @@ mod tests {
         ]);
         assert_eq!(yard, expected);
     }
+
+    #[test]
+    fn act_with_cranes() {
+        let yard = Yard(vec![
+            vec![Crate('A'), Crate('B'), Crate('C'), Crate('D'), Crate('E')],
+            vec![],
+        ]);
+        let instruction = Instruction {
+            count: 5,
+            src: 0,
+            dst: 1,
+            kind: MoveKind::Individual,
+        };
+        let stack = |kind| {
+            let mut yard = yard.clone();
+            yard.act_with(&Crane::new(kind), instruction.clone())
+                .expect("act must succeed");
+            yard.0[1].iter().map(|Crate(c)| c).collect::<String>()
+        };
+        assert_eq!(stack(MoveKind::Individual), "EDCBA");
+        assert_eq!(stack(MoveKind::Grouped), "ABCDE");
+        assert_eq!(stack(MoveKind::Chunked(2)), "DEBCA");
+        assert_eq!(stack(MoveKind::Chunked(0)), "ABCDE");
+        assert_eq!(stack(MoveKind::Rotated), "EABCD");
+    }
+
+    #[test]
+    fn act_out_of_reach() {
+        let mut yard = Yard(vec![vec![Crate('A')], vec![], vec![]]);
+        let crane = Crane::new(MoveKind::Grouped).with_reach([0, 1]);
+        let instruction = Instruction {
+            count: 1,
+            src: 0,
+            dst: 2,
+            kind: MoveKind::Individual,
+        };
+        assert!(matches!(
+            yard.act_with(&crane, instruction),
+            Err(InstructionError::OutOfReach(2))
+        ));
+    }
+
+    #[test]
+    fn dry_run() {
+        let yard = Yard(vec![
+            vec![Crate('Z'), Crate('N')],
+            vec![Crate('M'), Crate('C'), Crate('D')],
+            vec![Crate('P')],
+        ]);
+        let instruction = |count, src, dst| Instruction {
+            count,
+            src,
+            dst,
+            kind: MoveKind::Individual,
+        };
+        let instructions = [
+            instruction(1, 1, 0),
+            instruction(3, 0, 2),
+            instruction(2, 1, 0),
+            instruction(1, 0, 1),
+        ];
+        let dry_run = yard.dry_run(&instructions);
+        assert!(dry_run.is_valid());
+        assert_eq!(
+            dry_run.required_heights,
+            vec![vec![2, 3, 0], vec![3, 2, 0], vec![0, 2, 0], vec![1, 0, 0]]
+        );
+
+        let instructions = [
+            instruction(4, 0, 1),
+            instruction(1, 3, 1),
+            instruction(1, 1, 3),
+            instruction(1, 2, 2),
+            instruction(2, 0, 2),
+        ];
+        let dry_run = yard.dry_run(&instructions);
+        assert!(matches!(
+            dry_run.failures[..],
+            [
+                (0, InstructionError::InsufficientCountError(4, 2)),
+                (1, InstructionError::SourceNotFound(3)),
+                (2, InstructionError::DestinationNotFound(3)),
+                (3, InstructionError::SameStack(2)),
+            ]
+        ));
+        // the yard must not have changed
+        assert_eq!(yard.topmost(), Some("NDP".to_string()));
+
+        // a move of more crates than there are is skipped, so asks nothing of the stacks
+        let instructions = [
+            instruction(4, 0, 1),
+            instruction(1, 0, 2),
+            instruction(2, 1, 0),
+        ];
+        let dry_run = yard.dry_run(&instructions);
+        assert!(matches!(
+            dry_run.failures[..],
+            [(0, InstructionError::InsufficientCountError(4, 2))]
+        ));
+        assert_eq!(
+            dry_run.required_heights,
+            vec![vec![1, 2, 0], vec![1, 2, 0], vec![0, 2, 0]]
+        );
+    }
+
+    #[test]
+    fn rope_yard_matches_vec_yard() {
+        let instructions = [
+            Instruction::new(3, 0, 1, MoveKind::Individual),
+            Instruction::new(4, 1, 2, MoveKind::Grouped),
+            Instruction::new(5, 2, 0, MoveKind::Chunked(2)),
+            Instruction::new(2, 0, 1, MoveKind::Rotated),
+            Instruction::new(4, 1, 2, MoveKind::Individual),
+        ];
+        let stacks = ["ABCDE", "FGH", "IJ"];
+        let mut vec_yard: Yard = Yard(stacks.map(|s| s.chars().map(Crate).collect()).into());
+        let mut rope_yard: Yard<stack::RopeStack> =
+            Yard(stacks.map(|s| s.chars().map(Crate).collect()).into());
+        for instruction in instructions {
+            vec_yard.act(instruction.clone()).expect("act must succeed");
+            rope_yard.act(instruction).expect("act must succeed");
+            assert_eq!(vec_yard.to_string(), rope_yard.to_string());
+        }
+    }
 }
@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
 use anyhow::{Context, Result};
-use day5::{Crate, Instruction, Yard};
+use day5::{Crane, Instruction, MoveKind, Yard};
 
 const INPUT: &str = include_str!("input.txt");
 
@@ -14,28 +14,25 @@ fn main() {
 }
 
 fn solve_part1(input: Input) -> Option<String> {
-    let mut yard: Yard = input.shipping_yard.into();
-    let instructions = input.instructions;
-
-    for instruction in instructions {
-        yard.act(instruction).ok()?
-    }
-    yard.topmost()
+    solve_with_crane(input, &Crane::new(MoveKind::Individual))
 }
 
 fn solve_part2(input: Input) -> Option<String> {
-    let mut yard: Yard = input.shipping_yard.into();
-    let instructions = input.instructions.into_iter().map(Instruction::to_grouped);
+    solve_with_crane(input, &Crane::new(MoveKind::Grouped))
+}
+
+fn solve_with_crane(input: Input, crane: &Crane) -> Option<String> {
+    let mut yard = input.shipping_yard;
 
-    for instruction in instructions {
-        yard.act(instruction).ok()?
+    for instruction in input.instructions {
+        yard.act_with(crane, instruction).ok()?
     }
     yard.topmost()
 }
 
 #[derive(Debug, Clone)]
 struct Input {
-    shipping_yard: Vec<(usize, Crate)>,
+    shipping_yard: Yard,
     instructions: Vec<Instruction>,
 }
 
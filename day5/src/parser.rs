@@ -1,11 +1,11 @@
-use crate::{Crate, Instruction, MoveKind};
+use crate::{Crate, Instruction, MoveKind, Yard};
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_until},
-    character::complete::{anychar, char as single_char, digit1, newline},
-    combinator::{all_consuming, map, map_res, value},
+    bytes::complete::tag,
+    character::complete::{anychar, char as single_char, digit1, newline, space0, space1},
+    combinator::{all_consuming, map, map_res, value, verify},
     multi::separated_list1,
-    sequence::{delimited, pair, separated_pair, terminated},
+    sequence::{delimited, pair, separated_pair, terminated, tuple},
     IResult,
 };
 
@@ -38,13 +38,51 @@ fn parse_crate(input: &str) -> IResult<&str, Option<Crate>> {
     )(input)
 }
 
+/// A row of crates. Trailing padding may be missing or longer than a single cell.
 fn crates(input: &str) -> IResult<&str, Vec<(usize, Crate)>> {
-    map(separated_list1(tag(" "), parse_crate), |v| {
-        (1usize..)
-            .zip(v.into_iter())
-            .filter_map(|(idx, c)| c.map(|c| (idx, c)))
-            .collect()
-    })(input)
+    map(
+        terminated(separated_list1(tag(" "), parse_crate), space0),
+        |v| {
+            (1usize..)
+                .zip(v)
+                .filter_map(|(idx, c)| c.map(|c| (idx, c)))
+                .collect()
+        },
+    )(input)
+}
+
+/// The numbered base row under the crates, returning the number of stacks. Numbers must count
+/// up from 1, and may be more than one digit wide.
+fn stack_numbers(input: &str) -> IResult<&str, usize> {
+    map(
+        verify(
+            delimited(
+                space0,
+                separated_list1(space1, map_res(digit1, str::parse::<usize>)),
+                space0,
+            ),
+            |numbers: &Vec<usize>| numbers.iter().copied().eq(1..=numbers.len()),
+        ),
+        |numbers| numbers.len(),
+    )(input)
+}
+
+/// The whole crate diagram, including the base row. Fails if a crate sits outside the
+/// numbered stacks.
+fn yard(input: &str) -> IResult<&str, Yard> {
+    map(
+        verify(
+            separated_pair(
+                map(separated_list1(newline, crates), |v| {
+                    v.into_iter().flatten().collect::<Vec<_>>()
+                }),
+                newline,
+                stack_numbers,
+            ),
+            |(crates, stack_count)| crates.iter().all(|(idx, _)| idx <= stack_count),
+        ),
+        |(crates, stack_count)| Yard::from_diagram(stack_count, crates),
+    )(input)
 }
 
 fn instruction_of_kind(kind: MoveKind) -> impl Fn(&str) -> IResult<&str, Instruction> {
@@ -68,18 +106,14 @@ fn instruction_of_kind(kind: MoveKind) -> impl Fn(&str) -> IResult<&str, Instruc
     }
 }
 
-type ParseOutput = (Vec<(usize, Crate)>, Vec<Instruction>);
+type ParseOutput = (Yard, Vec<Instruction>);
 
 pub fn parse_of_move_kind(move_kind: MoveKind) -> impl Fn(&str) -> IResult<&str, ParseOutput> {
     move |input: &str| {
-        let (input, crates) = map(terminated(separated_list1(newline, crates), newline), |v| {
-            v.into_iter().flatten().collect()
-        })(input)?;
-        let (input, _) = terminated(take_until("\n"), newline)(input)?;
-        let (input, _) = terminated(take_until("\n"), newline)(input)?;
+        let (input, yard) = terminated(yard, tuple((newline, space0, newline)))(input)?;
         let (input, directions) =
             all_consuming(separated_list1(newline, instruction_of_kind(move_kind)))(input)?;
-        Ok((input, (crates, directions)))
+        Ok((input, (yard, directions)))
     }
 }
 
@@ -120,14 +154,11 @@ move 1 from 1 to 2";
 
         let (_, got) = parse(input).expect("input must parse");
         let expected = (
-            vec![
-                (2, c!('D')),
-                (1, c!('N')),
-                (2, c!('C')),
-                (1, c!('Z')),
-                (2, c!('M')),
-                (3, c!('P')),
-            ],
+            Yard(vec![
+                vec![c!('Z'), c!('N')],
+                vec![c!('M'), c!('C'), c!('D')],
+                vec![c!('P')],
+            ]),
             vec![
                 i!(1, 2, 1, Individual),
                 i!(3, 1, 3, Individual),
@@ -137,4 +168,61 @@ move 1 from 1 to 2";
         );
         assert_eq!(got, expected);
     }
+
+    #[test]
+    fn round_trip() {
+        let diagram = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 ";
+        let input = format!("{diagram}\n\nmove 1 from 2 to 1");
+        let (_, (yard, _)) = parse(&input).expect("input must parse");
+        assert_eq!(yard.to_string(), diagram);
+    }
+
+    #[test]
+    fn stripped_trailing_whitespace() {
+        let input = "    [D]
+[N] [C]
+[Z] [M] [P]  
+ 1   2   3   4
+
+move 1 from 2 to 4";
+        let (_, (yard, _)) = parse(input).expect("input must parse");
+        assert_eq!(
+            yard,
+            Yard(vec![
+                vec![c!('Z'), c!('N')],
+                vec![c!('M'), c!('C'), c!('D')],
+                vec![c!('P')],
+                vec![],
+            ])
+        );
+    }
+
+    #[test]
+    fn multi_digit_stacks() {
+        let input = "                                [A]
+[B] [C] [D] [E] [F] [G] [H] [I] [J] [K] [L]
+ 1   2   3   4   5   6   7   8   9  10  11 
+
+move 1 from 11 to 9";
+        let (_, (yard, instructions)) = parse(input).expect("input must parse");
+        assert_eq!(yard.to_string().lines().last(), input.lines().nth(2));
+        assert_eq!(instructions, vec![i!(1, 11, 9, Individual)]);
+    }
+
+    #[test]
+    fn invalid_stack_numbers() {
+        let missing_stack = "[A] [B] [C]
+ 1   2 
+
+move 1 from 1 to 2";
+        assert!(parse(missing_stack).is_err());
+        let out_of_order = "[A] [B]
+ 1   3 
+
+move 1 from 1 to 2";
+        assert!(parse(out_of_order).is_err());
+    }
 }
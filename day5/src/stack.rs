@@ -0,0 +1,216 @@
+use std::rc::Rc;
+
+use crate::Crate;
+
+/// Storage for a single stack of crates, bottom first. Moves are expressed as whole-group
+/// operations so that each representation can do them as cheaply as it is able.
+pub trait Stack: Default + FromIterator<Crate> {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// The crate `level` places up from the bottom
+    fn get(&self, level: usize) -> Option<&Crate>;
+    fn top(&self) -> Option<&Crate> {
+        self.get(self.len().checked_sub(1)?)
+    }
+    /// Remove the topmost `count` crates, which must not be more than the stack holds. They are
+    /// returned in the same bottom-to-top order.
+    fn split_top(&mut self, count: usize) -> Self;
+    /// Place a whole stack on top of this one, keeping its order
+    fn append(&mut self, other: Self);
+    /// Turn the stack upside down
+    fn reverse(&mut self);
+}
+
+impl Stack for Vec<Crate> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn get(&self, level: usize) -> Option<&Crate> {
+        <[Crate]>::get(self, level)
+    }
+
+    fn split_top(&mut self, count: usize) -> Self {
+        self.split_off(Vec::len(self) - count)
+    }
+
+    fn append(&mut self, mut other: Self) {
+        Vec::append(self, &mut other)
+    }
+
+    fn reverse(&mut self) {
+        <[Crate]>::reverse(self)
+    }
+}
+
+/// A view into a shared run of crates, possibly read back to front
+#[derive(Debug, Clone)]
+struct Chunk {
+    crates: Rc<[Crate]>,
+    start: usize,
+    end: usize,
+    reversed: bool,
+}
+
+impl Chunk {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    fn get(&self, level: usize) -> Option<&Crate> {
+        if level >= self.len() {
+            return None;
+        }
+        let idx = if self.reversed {
+            self.end - 1 - level
+        } else {
+            self.start + level
+        };
+        self.crates.get(idx)
+    }
+
+    /// Split off the topmost `count` crates of this chunk, leaving the rest behind
+    fn split_top(&mut self, count: usize) -> Self {
+        let mut top = self.clone();
+        if self.reversed {
+            top.end = self.start + count;
+            self.start += count;
+        } else {
+            top.start = self.end - count;
+            self.end -= count;
+        }
+        top
+    }
+}
+
+/// A stack stored as a list of chunks sharing their crates. Splitting, appending and reversing
+/// only touch the chunks, never the crates inside them, so moving a large group costs the same
+/// as moving a small one. The price is fragmentation: each move may add a chunk or two.
+#[derive(Debug, Clone, Default)]
+pub struct RopeStack {
+    chunks: Vec<Chunk>,
+    len: usize,
+}
+
+impl FromIterator<Crate> for RopeStack {
+    fn from_iter<T: IntoIterator<Item = Crate>>(iter: T) -> Self {
+        let crates: Rc<[Crate]> = iter.into_iter().collect();
+        let len = crates.len();
+        let chunks = if len == 0 {
+            vec![]
+        } else {
+            vec![Chunk {
+                crates,
+                start: 0,
+                end: len,
+                reversed: false,
+            }]
+        };
+        Self { chunks, len }
+    }
+}
+
+impl PartialEq for RopeStack {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && (0..self.len).all(|level| Stack::get(self, level) == Stack::get(other, level))
+    }
+}
+impl Eq for RopeStack {}
+
+impl Stack for RopeStack {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, mut level: usize) -> Option<&Crate> {
+        for chunk in self.chunks.iter() {
+            if level < chunk.len() {
+                return chunk.get(level);
+            }
+            level -= chunk.len();
+        }
+        None
+    }
+
+    fn top(&self) -> Option<&Crate> {
+        let chunk = self.chunks.last()?;
+        chunk.get(chunk.len() - 1)
+    }
+
+    fn split_top(&mut self, count: usize) -> Self {
+        let mut taken = Vec::new();
+        let mut remaining = count;
+        while remaining > 0 {
+            let mut chunk = self
+                .chunks
+                .pop()
+                .expect("cannot split off more crates than the stack holds");
+            if chunk.len() > remaining {
+                taken.push(chunk.split_top(remaining));
+                self.chunks.push(chunk);
+                remaining = 0;
+            } else {
+                remaining -= chunk.len();
+                taken.push(chunk);
+            }
+        }
+        // we took the chunks from the top down, but stacks are stored bottom first
+        taken.reverse();
+        self.len -= count;
+        Self {
+            chunks: taken,
+            len: count,
+        }
+    }
+
+    fn append(&mut self, other: Self) {
+        self.len += other.len;
+        self.chunks.extend(other.chunks);
+    }
+
+    fn reverse(&mut self) {
+        self.chunks.reverse();
+        for chunk in self.chunks.iter_mut() {
+            chunk.reversed = !chunk.reversed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crates(s: &str) -> impl Iterator<Item = Crate> + '_ {
+        s.chars().map(Crate)
+    }
+
+    fn contents(stack: &RopeStack) -> String {
+        (0..stack.len())
+            .map(|level| stack.get(level).unwrap().0)
+            .collect()
+    }
+
+    #[test]
+    fn rope_stack_moves() {
+        let mut a: RopeStack = crates("ABCDEF").collect();
+        let mut b: RopeStack = crates("XY").collect();
+        let mut top = a.split_top(4);
+        assert_eq!(contents(&a), "AB");
+        assert_eq!(contents(&top), "CDEF");
+        top.reverse();
+        assert_eq!(contents(&top), "FEDC");
+        b.append(top);
+        assert_eq!(contents(&b), "XYFEDC");
+        let mut top = b.split_top(3);
+        assert_eq!(contents(&top), "EDC");
+        top.reverse();
+        a.append(top);
+        assert_eq!(contents(&a), "ABCDE");
+        assert_eq!(a.top(), Some(&Crate('E')));
+        assert_eq!(a, crates("ABCDE").collect());
+        assert_eq!(contents(&b), "XYF");
+    }
+}
@@ -1 +1,2 @@
+pub mod marker;
 pub mod parser;
@@ -1,22 +1,30 @@
-use anyhow::Result;
+use std::io::Read;
+
+use anyhow::{Context, Result};
+use day6::marker::read_markers;
 
 const INPUT: &str = include_str!("input.txt");
 
 fn main() {
     let input = INPUT;
-    let part1 = solve_part1(input).expect("part1 must have a solution");
+    let part1 = solve_part1(input.as_bytes()).expect("part1 must have a solution");
     println!("part1: {part1}");
-    let part2 = solve_part2(input).expect("part2 must have a solution");
+    let part2 = solve_part2(input.as_bytes()).expect("part2 must have a solution");
     println!("part2: {part2}");
 }
 
-fn solve_part1(s: &'static str) -> Result<usize> {
-    let (_, result) = day6::parser::line_through_start_code(s)?;
-    Ok(result.len())
+fn first_marker(signal: impl Read, size: usize) -> Result<usize> {
+    let position = read_markers(signal, size)
+        .next()
+        .context("signal must contain a marker")??;
+    Ok(position)
+}
+
+fn solve_part1(signal: impl Read) -> Result<usize> {
+    first_marker(signal, 4)
 }
-fn solve_part2(s: &'static str) -> Result<usize> {
-    let (_, result) = day6::parser::line_through_message_code(s)?;
-    Ok(result.len())
+fn solve_part2(signal: impl Read) -> Result<usize> {
+    first_marker(signal, 14)
 }
 
 #[cfg(test)]
@@ -25,7 +33,7 @@ mod tests {
 
     #[test]
     fn solve_part1() {
-        let part1 = super::solve_part1(INPUT).expect("must have a solution");
+        let part1 = super::solve_part1(INPUT.as_bytes()).expect("must have a solution");
         assert_eq!(part1, 7);
     }
 }
@@ -0,0 +1,342 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, BufReader, Bytes, Read},
+    ops::Range,
+};
+
+/// Anything that can be counted in a marker window
+pub trait Symbol: Copy {
+    /// The symbol's code point, so bytes and chars share the same `'a'..='z'` range
+    fn code(self) -> u32;
+}
+
+impl Symbol for u8 {
+    fn code(self) -> u32 {
+        self as u32
+    }
+}
+
+impl Symbol for char {
+    fn code(self) -> u32 {
+        self as u32
+    }
+}
+
+/// Symbols counted in the detector's fixed table, one slot each; anything else goes in a map
+const TABLE: std::ops::RangeInclusive<u32> = 'a' as u32..='z' as u32;
+
+/// Watches a signal one symbol at a time for a window of `size` symbols that are all different.
+///
+/// Keeps a count of each symbol in the window, along with how many symbols in it are repeats, so
+/// each symbol costs O(1) no matter the window size. Lowercase letters, all the puzzle uses, are
+/// counted in a 26 slot table; any other symbol is counted in a map that only holds symbols
+/// currently in the window.
+#[derive(Debug, Clone)]
+pub struct MarkerDetector {
+    size: usize,
+    window: VecDeque<u32>,
+    counts: [usize; 26],
+    others: HashMap<u32, usize>,
+    repeats: usize,
+    position: usize,
+}
+
+impl MarkerDetector {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            window: VecDeque::with_capacity(size),
+            counts: [0; 26],
+            others: HashMap::new(),
+            repeats: 0,
+            position: 0,
+        }
+    }
+
+    /// How many symbols have been pushed so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Whether the last `size` symbols pushed are all different
+    pub fn is_marker(&self) -> bool {
+        self.window.len() == self.size && self.repeats == 0
+    }
+
+    /// Slide the window on by one symbol, returning whether it now covers a marker
+    pub fn push(&mut self, symbol: impl Symbol) -> bool {
+        self.position += 1;
+        if self.size == 0 {
+            return true;
+        }
+        if self.window.len() == self.size {
+            let oldest = self
+                .window
+                .pop_front()
+                .expect("a full window is never empty");
+            let count = self.count_mut(oldest);
+            *count -= 1;
+            let left = *count;
+            if left > 0 {
+                self.repeats -= 1;
+            } else if !TABLE.contains(&oldest) {
+                self.others.remove(&oldest);
+            }
+        }
+        let code = symbol.code();
+        let count = self.count_mut(code);
+        *count += 1;
+        if *count > 1 {
+            self.repeats += 1;
+        }
+        self.window.push_back(code);
+        self.is_marker()
+    }
+
+    fn count_mut(&mut self, code: u32) -> &mut usize {
+        if TABLE.contains(&code) {
+            &mut self.counts[(code - TABLE.start()) as usize]
+        } else {
+            self.others.entry(code).or_insert(0)
+        }
+    }
+}
+
+/// Find the end of the first window of `size` different symbols, as the number of symbols read
+/// through it. This is the puzzle's answer.
+pub fn find_marker<T: Symbol>(signal: impl IntoIterator<Item = T>, size: usize) -> Option<usize> {
+    if size == 0 {
+        return Some(0);
+    }
+    let mut detector = MarkerDetector::new(size);
+    signal
+        .into_iter()
+        .find(|&symbol| detector.push(symbol))
+        .map(|_| detector.position())
+}
+
+/// Yields the end position of every marker in a signal as soon as the symbol completing it is
+/// read, holding no more of the signal than the window. An empty window is a marker everywhere,
+/// including at position 0 before anything is read, as `find_marker` has it.
+#[derive(Debug, Clone)]
+pub struct Markers<I> {
+    signal: I,
+    detector: MarkerDetector,
+    started: bool,
+}
+
+impl<T: Symbol, I: Iterator<Item = T>> Iterator for Markers<I> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !std::mem::replace(&mut self.started, true) && self.detector.is_marker() {
+            return Some(0);
+        }
+        for symbol in self.signal.by_ref() {
+            if self.detector.push(symbol) {
+                return Some(self.detector.position());
+            }
+        }
+        None
+    }
+}
+
+pub fn markers<I: IntoIterator>(signal: I, size: usize) -> Markers<I::IntoIter>
+where
+    I::Item: Symbol,
+{
+    Markers {
+        signal: signal.into_iter(),
+        detector: MarkerDetector::new(size),
+        started: false,
+    }
+}
+
+/// Like `Markers`, but pulling bytes from a reader as they are needed. Stops after the first
+/// read error.
+#[derive(Debug)]
+pub struct ReadMarkers<R> {
+    bytes: Bytes<BufReader<R>>,
+    detector: MarkerDetector,
+    started: bool,
+    failed: bool,
+}
+
+impl<R: Read> Iterator for ReadMarkers<R> {
+    type Item = io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        if !std::mem::replace(&mut self.started, true) && self.detector.is_marker() {
+            return Some(Ok(0));
+        }
+        for byte in self.bytes.by_ref() {
+            match byte {
+                Ok(byte) => {
+                    if self.detector.push(byte) {
+                        return Some(Ok(self.detector.position()));
+                    }
+                }
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+pub fn read_markers<R: Read>(reader: R, size: usize) -> ReadMarkers<R> {
+    ReadMarkers {
+        bytes: BufReader::new(reader).bytes(),
+        detector: MarkerDetector::new(size),
+        started: false,
+        failed: false,
+    }
+}
+
+/// Every window of `size` different symbols in the signal, as positions into it. Windows may
+/// overlap.
+pub fn marker_windows<I: IntoIterator>(signal: I, size: usize) -> Vec<Range<usize>>
+where
+    I::Item: Symbol,
+{
+    markers(signal, size).map(|end| end - size..end).collect()
+}
+
+/// A message in a transmission: the marker announcing it, and everything after it up to the
+/// next marker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message<'a> {
+    /// Where the payload starts, counted in characters like marker positions
+    pub offset: usize,
+    pub marker: &'a str,
+    pub payload: &'a str,
+}
+
+/// Split a transmission into messages. A new marker must be made entirely of characters after
+/// the previous one, so markers never overlap. Anything before the first marker is dropped.
+pub fn messages(signal: &str, size: usize) -> Vec<Message<'_>> {
+    let mut marker_ends = Vec::new();
+    let mut detector = MarkerDetector::new(size);
+    for (position, c) in (1..).zip(signal.chars()) {
+        if detector.push(c) {
+            marker_ends.push(position);
+            detector = MarkerDetector::new(size);
+        }
+    }
+
+    // the byte index of every character position, so positions can be turned into slices
+    let boundaries: Vec<usize> = signal
+        .char_indices()
+        .map(|(idx, _)| idx)
+        .chain([signal.len()])
+        .collect();
+    let end_of_signal = boundaries.len() - 1;
+    marker_ends
+        .iter()
+        .enumerate()
+        .map(|(i, &end)| {
+            let payload_end = marker_ends
+                .get(i + 1)
+                .map_or(end_of_signal, |next_end| next_end - size);
+            Message {
+                offset: end,
+                marker: &signal[boundaries[end - size]..boundaries[end]],
+                payload: &signal[boundaries[end]..boundaries[payload_end]],
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    #[test]
+    fn find_marker() {
+        let signals = [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+        ];
+        for (signal, packet, message) in signals {
+            assert_eq!(super::find_marker(signal.chars(), 4), Some(packet));
+            assert_eq!(super::find_marker(signal.bytes(), 4), Some(packet));
+            assert_eq!(super::find_marker(signal.chars(), 14), Some(message));
+            assert_eq!(super::find_marker(signal.bytes(), 14), Some(message));
+        }
+        assert_eq!(super::find_marker("aaaa".chars(), 2), None);
+        assert_eq!(super::find_marker("abc".chars(), 4), None);
+        assert_eq!(super::find_marker("abc".chars(), 1), Some(1));
+
+        // symbols outside the letter table are counted too, and dropped once out of the window
+        assert_eq!(super::find_marker("aA1aA1é€b".chars(), 4), Some(7));
+        assert_eq!(
+            super::find_marker("zZzZ\u{10FFFF}\u{10FFFF}x".chars(), 2),
+            Some(2)
+        );
+        let mut detector = super::MarkerDetector::new(2);
+        for c in "€é€é".chars().chain(('\u{4E00}'..'\u{5E00}').step_by(2)) {
+            detector.push(c);
+        }
+        assert!(detector.is_marker());
+        assert_eq!(detector.others.len(), 2);
+    }
+
+    #[test]
+    fn read_markers() {
+        // the stream never ends, so markers must come out before it is read in full
+        let stream = "aabcd".as_bytes().chain(io::repeat(b'x'));
+        let mut markers = super::read_markers(stream, 4);
+        assert_eq!(markers.next().unwrap().unwrap(), 5);
+
+        let stream = "abcabcdd".as_bytes();
+        let got = super::read_markers(stream, 3)
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(got, vec![3, 4, 5, 6, 7]);
+        assert_eq!(
+            super::markers("abcabcdd".chars(), 3).collect::<Vec<_>>(),
+            got
+        );
+
+        // an empty window is a marker before anything is read, as find_marker says
+        let got = super::read_markers("abc".as_bytes(), 0)
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(got, vec![0, 1, 2, 3]);
+        assert_eq!(super::markers("abc".chars(), 0).collect::<Vec<_>>(), got);
+        assert_eq!(
+            super::markers("".chars(), 0).next(),
+            super::find_marker("".chars(), 0)
+        );
+    }
+
+    #[test]
+    fn messages() {
+        assert_eq!(super::marker_windows("aabcbd".chars(), 3), vec![1..4, 3..6]);
+
+        let got = super::messages("aaabcdzzzzefghzz", 4);
+        assert_eq!(
+            got,
+            vec![
+                super::Message {
+                    offset: 6,
+                    marker: "abcd",
+                    payload: "zzz",
+                },
+                super::Message {
+                    offset: 13,
+                    marker: "zefg",
+                    payload: "hzz",
+                },
+            ]
+        );
+    }
+}
@@ -1,59 +1,36 @@
-use std::collections::HashSet;
-
 use nom::{
     self,
-    character::complete::anychar,
-    combinator::{map, recognize, verify},
-    multi::many_till,
+    error::{Error, ErrorKind},
     IResult,
 };
 
-fn no_repeats(s: &str) -> bool {
-    let mut set = HashSet::new();
-    for char in s.chars() {
-        set.insert(char);
-    }
-    s.len() == set.len()
-}
+use crate::marker::find_marker;
 
-fn consecutive_characters_without_repeat(count: usize) -> impl Fn(&str) -> IResult<&str, String> {
+/// Recognize everything up to and including the first `count` consecutive characters without a
+/// repeat, in a single pass over the input
+fn line_through_marker(count: usize) -> impl Fn(&str) -> IResult<&str, &str> {
     move |input: &str| {
-        map(
-            verify(nom::multi::count(anychar, count), |v: &Vec<char>| {
-                no_repeats(&v.iter().collect::<String>())
-            }),
-            |v| v.into_iter().collect(),
-        )(input)
+        let chars = find_marker(input.chars(), count)
+            .ok_or_else(|| nom::Err::Error(Error::new(input, ErrorKind::ManyTill)))?;
+        let end = input
+            .char_indices()
+            .nth(chars)
+            .map_or(input.len(), |(idx, _)| idx);
+        let (through, rest) = input.split_at(end);
+        Ok((rest, through))
     }
 }
 
-fn start_code(input: &str) -> IResult<&str, String> {
-    consecutive_characters_without_repeat(4)(input)
-}
-
-fn message_code(input: &str) -> IResult<&str, String> {
-    consecutive_characters_without_repeat(14)(input)
-}
-
 pub fn line_through_start_code(input: &str) -> IResult<&str, &str> {
-    recognize(many_till(anychar, start_code))(input)
+    line_through_marker(4)(input)
 }
 
 pub fn line_through_message_code(input: &str) -> IResult<&str, &str> {
-    recognize(many_till(anychar, message_code))(input)
+    line_through_marker(14)(input)
 }
 
 #[cfg(test)]
 mod tests {
-    #[test]
-    fn start_code() {
-        let (_, result) = super::start_code("abcd").unwrap();
-        assert_eq!(result, "abcd".to_string());
-        let (rest, result) = super::start_code("abcdefg").unwrap();
-        assert_eq!((rest, result), ("efg", "abcd".to_string()));
-        assert!(matches!(super::start_code("aabc"), Err(_)))
-    }
-
     #[test]
     fn line_through_start_code() {
         let (_, result) = super::line_through_start_code("abcd").unwrap();
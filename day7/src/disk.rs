@@ -0,0 +1,139 @@
+use std::{fs, io, path::Path};
+
+use crate::{
+    instructions::{DirName, Instruction, ListOutput},
+    FileSystem,
+};
+
+/// The transcript of exploring a real directory as the puzzle's device does: `$ cd /`, then an
+/// `ls` of each directory before changing into each of its subdirectories in name order.
+///
+/// Symbolic links are skipped rather than followed, as are entries that are neither files nor
+/// directories, and entries whose names hold a line break and so could not appear in a
+/// transcript. Names that are not valid UTF-8 appear in the transcript with `U+FFFD` in place of
+/// the invalid bytes. Sizes are file lengths in bytes.
+pub fn transcript(root: &Path) -> io::Result<Vec<Instruction>> {
+    let mut instructions = vec![Instruction::ChangeDir(DirName::Root.into())];
+    explore(root, &mut instructions)?;
+    Ok(instructions)
+}
+
+fn explore(dir: &Path, instructions: &mut Vec<Instruction>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| {
+            let entry = entry?;
+            // the name is only for the transcript; the path still reaches names it had to mangle
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = entry.path();
+            let metadata = path.symlink_metadata()?;
+            Ok((name, path, metadata))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.retain(|(name, _, _)| !name.contains(['\n', '\r']));
+    entries.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+
+    let mut subdirs = vec![];
+    let outputs = entries
+        .into_iter()
+        .filter_map(|(name, path, metadata)| {
+            if metadata.is_dir() {
+                subdirs.push((name.clone(), path));
+                Some(ListOutput::Dir(name))
+            } else if metadata.is_file() {
+                Some(ListOutput::File(metadata.len(), name))
+            } else {
+                None
+            }
+        })
+        .collect();
+    instructions.push(Instruction::List(outputs));
+
+    for (name, path) in subdirs {
+        instructions.push(Instruction::ChangeDir(DirName::Name(name).into()));
+        explore(&path, instructions)?;
+        instructions.push(Instruction::ChangeDir(DirName::Parent.into()));
+    }
+    Ok(())
+}
+
+impl FileSystem {
+    /// Reconstruct a real directory, going by way of its transcript
+    pub fn from_disk(root: &Path) -> io::Result<Self> {
+        Self::try_from(transcript(root)?).map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::parser;
+
+    /// A scratch directory that removes itself, since there is no temp-dir crate to lean on
+    struct Scratch(PathBuf);
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn from_disk() {
+        let scratch =
+            Scratch(std::env::temp_dir().join(format!("day7-disk-{}", std::process::id())));
+        let root = &scratch.0;
+        fs::create_dir_all(root.join("a/e")).unwrap();
+        fs::create_dir_all(root.join("empty dir")).unwrap();
+        fs::write(root.join("b.txt"), [0; 1400]).unwrap();
+        fs::write(root.join("a/f"), [0; 291]).unwrap();
+        fs::write(root.join("a/e/i"), [0; 58]).unwrap();
+
+        let instructions = transcript(root).unwrap();
+        let text = instructions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let expected = "$ cd /
+$ ls
+dir a
+1400 b.txt
+dir empty dir
+$ cd a
+$ ls
+dir e
+291 f
+$ cd e
+$ ls
+58 i
+$ cd ..
+$ cd ..
+$ cd empty dir
+$ ls
+$ cd ..";
+        assert_eq!(text, expected);
+        let (_, parsed) = parser::parse(&text).expect("transcript must parse");
+        assert_eq!(parsed, instructions);
+
+        let filesystem = FileSystem::from_disk(root).unwrap();
+        assert_eq!(filesystem.root().size(), 1400 + 291 + 58);
+        assert_eq!(filesystem.sizes()["/a"], 291 + 58);
+        assert_eq!(filesystem.sizes()["/empty dir"], 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_names() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let scratch =
+            Scratch(std::env::temp_dir().join(format!("day7-utf8-{}", std::process::id())));
+        let bad = scratch.0.join(OsStr::from_bytes(b"bad\xff"));
+        fs::create_dir_all(&bad).unwrap();
+        fs::write(bad.join("f"), [0; 7]).unwrap();
+
+        let filesystem = FileSystem::from_disk(&scratch.0).unwrap();
+        assert_eq!(filesystem.sizes()["/bad\u{FFFD}"], 7);
+    }
+}
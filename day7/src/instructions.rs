@@ -1,21 +1,96 @@
-#[derive(Debug, Clone)]
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Instruction {
     /// A "cd" command
-    ChangeDir(DirName),
+    ChangeDir(Target),
     /// An "ls" command. Contains its output as list of string
     List(Vec<ListOutput>),
+    /// A "mkdir" command. The directory's parent must already exist.
+    MakeDir(Target),
+    /// An "rm" command, which removes a file or a whole directory tree
+    Remove(Target),
+    /// A "touch" command, optionally giving the file's size. Without a size a new file is empty
+    /// and an existing one is left as it is.
+    Touch(Target, Option<u64>),
 }
 
 /// The output from a list command
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ListOutput {
     Dir(String),
     File(u64, String),
 }
 
-#[derive(Debug, Clone)]
+/// One segment of a path
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DirName {
     Root,
     Parent,
     Name(String),
 }
+
+/// The path a command acts on, such as `/x/y`, `a/b` or `../d`. Absolute paths start with
+/// `DirName::Root`, and an empty path is the current directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target(pub Vec<DirName>);
+
+impl From<DirName> for Target {
+    fn from(name: DirName) -> Self {
+        Self(vec![name])
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (absolute, segments) = match self.0.split_first() {
+            Some((DirName::Root, rest)) => (true, rest),
+            _ => (false, self.0.as_slice()),
+        };
+        let segments = segments
+            .iter()
+            .map(|segment| match segment {
+                DirName::Root => "/",
+                DirName::Parent => "..",
+                DirName::Name(name) => name,
+            })
+            .collect::<Vec<_>>();
+        match (absolute, segments.is_empty()) {
+            (true, _) => write!(f, "/{}", segments.join("/")),
+            (false, true) => write!(f, "."),
+            (false, false) => write!(f, "{}", segments.join("/")),
+        }
+    }
+}
+
+/// Renders the command as it appears in a transcript, followed by any output
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ChangeDir(target) => write!(f, "$ cd {target}"),
+            Self::List(outputs) => {
+                write!(f, "$ ls")?;
+                for output in outputs {
+                    match output {
+                        ListOutput::Dir(name) => write!(f, "\ndir {name}")?,
+                        ListOutput::File(size, name) => write!(f, "\n{size} {name}")?,
+                    }
+                }
+                Ok(())
+            }
+            Self::MakeDir(target) => write!(f, "$ mkdir {target}"),
+            Self::Remove(target) => write!(f, "$ rm {target}"),
+            Self::Touch(target, Some(size)) => write!(f, "$ touch {size} {target}"),
+            Self::Touch(target, None) => {
+                // a name like `5 x` would read back as a size; `./` keeps it a name
+                let target = target.to_string();
+                let escape = if target.starts_with(|c: char| c.is_ascii_digit()) {
+                    "./"
+                } else {
+                    ""
+                };
+                write!(f, "$ touch {escape}{target}")
+            }
+        }
+    }
+}
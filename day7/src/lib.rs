@@ -1,10 +1,15 @@
+pub mod disk;
 pub mod instructions;
 pub mod parser;
+pub mod query;
+pub mod render;
 
-use instructions::{DirName, Instruction, ListOutput};
+use instructions::{DirName, Instruction, ListOutput, Target};
 
 use std::collections::BTreeMap;
 
+use thiserror::Error;
+
 pub enum FileEntry {
     Dir { name: String },
     File { name: String, size: u64 },
@@ -19,64 +24,417 @@ impl From<ListOutput> for FileEntry {
     }
 }
 
-/// A pair of (name, size)
-type FileSystemEntry = (String, u64);
+/// Ways a transcript can contradict itself
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TranscriptError {
+    #[error("Used directory {0} which was never listed or made")]
+    UnlistedDirectory(String),
+    #[error("Changed to the parent of the root directory")]
+    ParentOfRoot,
+    #[error("File {path} was listed with size {first} and then with size {second}")]
+    ConflictingFileSize {
+        path: String,
+        first: u64,
+        second: u64,
+    },
+    #[error("{0} was listed as both a file and a directory")]
+    ConflictingKind(String),
+    #[error("Made directory {0} which already exists")]
+    AlreadyExists(String),
+    #[error("Removed {0} which does not exist")]
+    NotFound(String),
+    #[error("Removed {0} while working inside it")]
+    RemovedWorkingDirectory(String),
+    #[error("{0} does not name a file or directory")]
+    InvalidTarget(Target),
+}
+
+/// Index of a directory in its `FileSystem`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DirId(usize);
 
-fn sum_size(entries: &[FileSystemEntry]) -> u64 {
-    entries.iter().map(|(_, size)| *size).sum::<u64>()
+impl DirId {
+    pub const ROOT: Self = Self(0);
 }
 
 #[derive(Debug, Clone)]
-pub struct FileSystem(BTreeMap<Vec<String>, Vec<FileSystemEntry>>);
+pub struct Directory {
+    name: String,
+    parent: Option<DirId>,
+    children: BTreeMap<String, DirId>,
+    files: BTreeMap<String, u64>,
+    /// Total size of every file in this directory and below it
+    size: u64,
+}
+
+impl Directory {
+    fn new(name: String, parent: Option<DirId>) -> Self {
+        Self {
+            name,
+            parent,
+            children: BTreeMap::new(),
+            files: BTreeMap::new(),
+            size: 0,
+        }
+    }
+    /// The directory's own name. The root's name is empty.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn parent(&self) -> Option<DirId> {
+        self.parent
+    }
+    pub fn children(&self) -> impl Iterator<Item = (&str, DirId)> {
+        self.children.iter().map(|(name, &id)| (name.as_str(), id))
+    }
+    pub fn files(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.files.iter().map(|(name, &size)| (name.as_str(), size))
+    }
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// A directory tree reconstructed from a terminal transcript. Directories live in an arena and
+/// refer to each other by `DirId`, with the root at `DirId::ROOT`.
+#[derive(Debug, Clone)]
+pub struct FileSystem {
+    dirs: Vec<Directory>,
+}
 
 impl FileSystem {
-    pub fn sizes(&self) -> BTreeMap<Vec<String>, u64> {
-        self.0.iter().fold(
-            BTreeMap::<Vec<String>, u64>::new(),
-            |mut acc, (dir, files)| {
-                let size = sum_size(files);
-                for i in 0..=dir.len() {
-                    acc.entry(dir[0..i].to_vec().clone())
-                        .and_modify(|v| *v += size)
-                        .or_insert(size);
-                }
-                acc
-            },
-        )
+    pub fn root(&self) -> &Directory {
+        &self.dirs[DirId::ROOT.0]
+    }
+
+    pub fn get(&self, id: DirId) -> &Directory {
+        &self.dirs[id.0]
+    }
+
+    /// Find a directory by its absolute path, such as `/a/e`
+    pub fn lookup(&self, path: &str) -> Option<DirId> {
+        path.split('/')
+            .filter(|segment| !segment.is_empty())
+            .try_fold(DirId::ROOT, |id, segment| {
+                self.get(id).children.get(segment).copied()
+            })
+    }
+
+    /// The absolute path of a directory, such as `/a/e`
+    pub fn path(&self, id: DirId) -> String {
+        let mut names = vec![];
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let dir = self.get(id);
+            names.push(dir.name.as_str());
+            current = dir.parent;
+        }
+        match names.len() {
+            1 => "/".to_string(),
+            _ => names.into_iter().rev().collect::<Vec<_>>().join("/"),
+        }
+    }
+
+    /// Every directory, depth first from the root with children in name order, along with its
+    /// depth below the root
+    pub fn iter(&self) -> impl Iterator<Item = (usize, DirId, &Directory)> + '_ {
+        let mut stack = vec![(0, DirId::ROOT)];
+        std::iter::from_fn(move || {
+            let (depth, id) = stack.pop()?;
+            let dir = self.get(id);
+            stack.extend(dir.children.values().rev().map(|&child| (depth + 1, child)));
+            Some((depth, id, dir))
+        })
+    }
+
+    /// Recursive size of every directory, by absolute path
+    pub fn sizes(&self) -> BTreeMap<String, u64> {
+        self.iter()
+            .map(|(_, id, dir)| (self.path(id), dir.size))
+            .collect()
+    }
+
+    fn add_dir(&mut self, parent: DirId, name: String) -> Result<DirId, TranscriptError> {
+        if self.get(parent).files.contains_key(&name) {
+            return Err(TranscriptError::ConflictingKind(
+                self.child_path(parent, &name),
+            ));
+        }
+        let next_id = DirId(self.dirs.len());
+        let id = *self.dirs[parent.0]
+            .children
+            .entry(name.clone())
+            .or_insert(next_id);
+        if id == next_id {
+            self.dirs.push(Directory::new(name, Some(parent)));
+        }
+        Ok(id)
+    }
+
+    fn add_file(&mut self, parent: DirId, name: String, size: u64) -> Result<(), TranscriptError> {
+        if self.get(parent).children.contains_key(&name) {
+            return Err(TranscriptError::ConflictingKind(
+                self.child_path(parent, &name),
+            ));
+        }
+        match self.get(parent).files.get(&name) {
+            Some(&first) if first != size => Err(TranscriptError::ConflictingFileSize {
+                path: self.child_path(parent, &name),
+                first,
+                second: size,
+            }),
+            Some(_) => Ok(()),
+            None => {
+                self.dirs[parent.0].files.insert(name, size);
+                Ok(())
+            }
+        }
+    }
+
+    /// Follow a path from `context` to the directory it leads to
+    fn resolve(&self, context: DirId, path: &[DirName]) -> Result<DirId, TranscriptError> {
+        path.iter().try_fold(context, |id, segment| match segment {
+            DirName::Root => Ok(DirId::ROOT),
+            DirName::Parent => self.get(id).parent.ok_or(TranscriptError::ParentOfRoot),
+            DirName::Name(name) => self
+                .get(id)
+                .children
+                .get(name)
+                .copied()
+                .ok_or_else(|| TranscriptError::UnlistedDirectory(self.child_path(id, name))),
+        })
+    }
+
+    /// Split a path into the directory holding its last segment, and that segment's name
+    fn resolve_parent(
+        &self,
+        context: DirId,
+        target: &Target,
+    ) -> Result<(DirId, String), TranscriptError> {
+        match target.0.split_last() {
+            Some((DirName::Name(name), path)) => Ok((self.resolve(context, path)?, name.clone())),
+            _ => Err(TranscriptError::InvalidTarget(target.clone())),
+        }
+    }
+
+    fn make_dir(&mut self, context: DirId, target: &Target) -> Result<(), TranscriptError> {
+        let (parent, name) = self.resolve_parent(context, target)?;
+        if self.get(parent).children.contains_key(&name) {
+            return Err(TranscriptError::AlreadyExists(
+                self.child_path(parent, &name),
+            ));
+        }
+        self.add_dir(parent, name).map(|_| ())
+    }
+
+    fn touch(
+        &mut self,
+        context: DirId,
+        target: &Target,
+        size: Option<u64>,
+    ) -> Result<(), TranscriptError> {
+        let (parent, name) = self.resolve_parent(context, target)?;
+        if self.get(parent).children.contains_key(&name) {
+            return Err(TranscriptError::ConflictingKind(
+                self.child_path(parent, &name),
+            ));
+        }
+        let files = &mut self.dirs[parent.0].files;
+        match (files.get_mut(&name), size) {
+            (Some(existing), Some(size)) => *existing = size,
+            (Some(_), None) => {}
+            (None, size) => {
+                files.insert(name, size.unwrap_or(0));
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove a file, or a directory and everything in it. A removed directory stays in the
+    /// arena but is cut loose from its parent, so nothing can reach it or count its size.
+    fn remove(&mut self, context: DirId, target: &Target) -> Result<(), TranscriptError> {
+        let (parent, name) = self.resolve_parent(context, target)?;
+        if self.dirs[parent.0].files.remove(&name).is_some() {
+            return Ok(());
+        }
+        let Some(&id) = self.get(parent).children.get(&name) else {
+            return Err(TranscriptError::NotFound(self.child_path(parent, &name)));
+        };
+        let mut current = Some(context);
+        while let Some(ancestor) = current {
+            if ancestor == id {
+                return Err(TranscriptError::RemovedWorkingDirectory(self.path(id)));
+            }
+            current = self.get(ancestor).parent;
+        }
+        self.dirs[parent.0].children.remove(&name);
+        self.dirs[id.0].parent = None;
+        Ok(())
+    }
+
+    fn child_path(&self, parent: DirId, name: &str) -> String {
+        match parent {
+            DirId::ROOT => format!("/{name}"),
+            _ => format!("{}/{name}", self.path(parent)),
+        }
+    }
+
+    /// Fill in every directory's recursive size. Children are always created after their
+    /// parents, so walking the arena backwards sees each directory before its parent.
+    fn compute_sizes(&mut self) {
+        for idx in (0..self.dirs.len()).rev() {
+            let dir = &mut self.dirs[idx];
+            dir.size += dir.files.values().sum::<u64>();
+            let (size, parent) = (dir.size, dir.parent);
+            if let Some(parent) = parent {
+                self.dirs[parent.0].size += size;
+            }
+        }
     }
 }
 
-impl From<Vec<Instruction>> for FileSystem {
-    fn from(instructions: Vec<Instruction>) -> Self {
-        let (_, map): (Vec<String>, BTreeMap<Vec<String>, Vec<FileSystemEntry>>) =
-            instructions.into_iter().fold(
-                (vec![], BTreeMap::new()),
-                |(mut context, mut map), instruction| {
-                    match instruction {
-                        Instruction::ChangeDir(DirName::Root) => {
-                            context.push("".to_string());
-                        }
-                        Instruction::ChangeDir(DirName::Parent) => {
-                            context.pop();
-                        }
-                        Instruction::ChangeDir(DirName::Name(target)) => {
-                            context.push(target);
-                        }
-                        Instruction::List(list_outputs) => {
-                            for file_entry in list_outputs {
-                                let file_entry: FileEntry = file_entry.into();
-                                if let FileEntry::File { name, size } = file_entry {
-                                    map.entry(context.clone())
-                                        .and_modify(|vec| vec.push((name.clone(), size)))
-                                        .or_insert_with(|| vec![(name, size)]);
-                                }
+impl TryFrom<Vec<Instruction>> for FileSystem {
+    type Error = TranscriptError;
+
+    fn try_from(instructions: Vec<Instruction>) -> Result<Self, Self::Error> {
+        let mut filesystem = Self {
+            dirs: vec![Directory::new(String::new(), None)],
+        };
+        let mut context = DirId::ROOT;
+        for instruction in instructions {
+            match instruction {
+                Instruction::ChangeDir(target) => {
+                    context = filesystem.resolve(context, &target.0)?;
+                }
+                Instruction::List(list_outputs) => {
+                    for file_entry in list_outputs {
+                        match file_entry.into() {
+                            FileEntry::Dir { name } => {
+                                filesystem.add_dir(context, name)?;
+                            }
+                            FileEntry::File { name, size } => {
+                                filesystem.add_file(context, name, size)?;
                             }
                         }
-                    };
-                    (context, map)
-                },
-            );
-        dbg!(map.clone());
-        Self(map)
+                    }
+                }
+                Instruction::MakeDir(target) => filesystem.make_dir(context, &target)?,
+                Instruction::Remove(target) => filesystem.remove(context, &target)?,
+                Instruction::Touch(target, size) => filesystem.touch(context, &target, size)?,
+            };
+        }
+        filesystem.compute_sizes();
+        Ok(filesystem)
+    }
+}
+
+/// The puzzle's example, shared by every module's tests
+#[cfg(test)]
+mod fixtures {
+    use crate::{parser, FileSystem, TranscriptError};
+
+    pub const INPUT: &str = include_str!("test_input.txt");
+
+    pub fn replay(input: &str) -> Result<FileSystem, TranscriptError> {
+        let (_, instructions) = parser::parse(input).expect("input must parse");
+        instructions.try_into()
+    }
+
+    pub fn filesystem() -> FileSystem {
+        replay(INPUT).expect("transcript must be consistent")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{filesystem, replay};
+
+    #[test]
+    fn tree() {
+        let filesystem = filesystem();
+        let e = filesystem.lookup("/a/e").expect("/a/e must exist");
+        assert_eq!(filesystem.path(e), "/a/e");
+        assert_eq!(filesystem.get(e).size(), 584);
+        assert_eq!(filesystem.root().size(), 48381165);
+        assert_eq!(filesystem.lookup("/a/x"), None);
+        let paths = filesystem
+            .iter()
+            .map(|(depth, id, _)| (depth, filesystem.path(id)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                (0, "/".to_string()),
+                (1, "/a".to_string()),
+                (2, "/a/e".to_string()),
+                (1, "/d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn inconsistent_transcripts() {
+        assert_eq!(
+            replay("$ cd /\n$ ls\ndir a\n$ cd b").unwrap_err(),
+            TranscriptError::UnlistedDirectory("/b".to_string())
+        );
+        assert_eq!(
+            replay("$ cd /\n$ ls\n1 a\n$ ls\n2 a").unwrap_err(),
+            TranscriptError::ConflictingFileSize {
+                path: "/a".to_string(),
+                first: 1,
+                second: 2
+            }
+        );
+        assert_eq!(
+            replay("$ cd /\n$ ls\n1 a\ndir a").unwrap_err(),
+            TranscriptError::ConflictingKind("/a".to_string())
+        );
+    }
+
+    #[test]
+    fn shell_commands() {
+        let input = "$ cd /
+$ mkdir a
+$ mkdir a/b
+$ cd a/b
+$ touch 100 x
+$ touch x
+$ cd /a
+$ touch 20 b/y
+$ mkdir /c
+$ cd ../c
+$ touch 5 z
+$ rm ../a/b/x";
+        let replayed = replay(input).expect("transcript must be consistent");
+        assert_eq!(replayed.sizes()["/a"], 20);
+        assert_eq!(replayed.root().size(), 25);
+
+        let replayed = replay(&format!("{input}\n$ rm /a")).unwrap();
+        assert_eq!(replayed.lookup("/a/b"), None);
+        assert_eq!(replayed.root().size(), 5);
+        assert_eq!(replayed.iter().count(), 2);
+
+        assert_eq!(
+            replay("$ cd /\n$ mkdir a\n$ mkdir a").unwrap_err(),
+            TranscriptError::AlreadyExists("/a".to_string())
+        );
+        assert_eq!(
+            replay("$ cd /\n$ mkdir a/b").unwrap_err(),
+            TranscriptError::UnlistedDirectory("/a".to_string())
+        );
+        assert_eq!(
+            replay("$ cd /\n$ rm a").unwrap_err(),
+            TranscriptError::NotFound("/a".to_string())
+        );
+        assert_eq!(
+            replay("$ mkdir a\n$ cd a\n$ rm /a").unwrap_err(),
+            TranscriptError::RemovedWorkingDirectory("/a".to_string())
+        );
+        assert_eq!(
+            replay("$ rm ..").unwrap_err(),
+            TranscriptError::InvalidTarget(DirName::Parent.into())
+        );
     }
 }
@@ -1,10 +1,16 @@
 use day7::instructions::Instruction;
+use day7::query::Query;
 use day7::FileSystem;
 
 use std::str::FromStr;
 
 const INPUT: &str = include_str!("input.txt");
 
+/// Directories at most this big count towards part 1
+const SMALL_DIR_LIMIT: u64 = 100000;
+const DISK_SIZE: u64 = 70000000;
+const UPDATE_SIZE: u64 = 30000000;
+
 fn main() {
     let input: Input = INPUT.parse().expect("input must parse");
     let part1 = solve_part1(input.clone());
@@ -14,27 +20,23 @@ fn main() {
 }
 
 fn solve_part1(input: Input) -> u64 {
-    let filesystem: FileSystem = input.0.into();
+    let filesystem = FileSystem::try_from(input.0).expect("transcript must be consistent");
     filesystem
-        .sizes()
+        .query(&Query::dirs().size(..=SMALL_DIR_LIMIT))
         .into_iter()
-        .filter_map(|(_, size)| if size <= 100000 { Some(size) } else { None })
+        .map(|hit| hit.size)
         .sum::<u64>()
 }
 
 fn solve_part2(input: Input) -> u64 {
-    let filesystem: FileSystem = input.0.into();
-    let sizes = filesystem.sizes();
-    let total_space: u64 = 70000000;
-    let used_space: u64 = *sizes
-        .get(&vec!["".to_string()])
-        .expect("the root must have a size");
-    let free_space = total_space - used_space;
-    let needed_space: u64 = 30000000;
-    let must_free = needed_space - free_space;
-    sizes
+    let filesystem = FileSystem::try_from(input.0).expect("transcript must be consistent");
+    let free_space = DISK_SIZE - filesystem.root().size();
+    let must_free = UPDATE_SIZE.saturating_sub(free_space);
+    // the puzzle only lets us delete a single directory
+    filesystem
+        .query(&Query::dirs().size(must_free..))
         .into_iter()
-        .filter_map(|(_, size)| if size >= must_free { Some(size) } else { None })
+        .map(|hit| hit.size)
         .min()
         .unwrap_or(must_free)
 }
@@ -54,29 +56,7 @@ impl FromStr for Input {
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "$ cd /
-$ ls
-dir a
-14848514 b.txt
-8504156 c.dat
-dir d
-$ cd a
-$ ls
-dir e
-29116 f
-2557 g
-62596 h.lst
-$ cd e
-$ ls
-584 i
-$ cd ..
-$ cd ..
-$ cd d
-$ ls
-4060174 j
-8033020 d.log
-5626152 d.ext
-7214296 k";
+    const INPUT: &str = include_str!("test_input.txt");
 
     #[test]
     fn solve_part1() {
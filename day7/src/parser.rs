@@ -1,63 +1,157 @@
-use crate::instructions::{DirName, Instruction, ListOutput};
+use crate::instructions::{DirName, Instruction, ListOutput, Target};
 use nom::{
     self,
     branch::alt,
-    bytes::complete::{is_a, tag, take_until},
-    character::complete::{digit1, newline},
-    combinator::map_res,
-    multi::separated_list1,
-    sequence::preceded,
+    bytes::complete::tag,
+    character::complete::{digit1, line_ending, multispace0, not_line_ending},
+    combinator::{all_consuming, map, map_res, opt, verify},
+    multi::{many0, separated_list1},
+    sequence::{pair, preceded, separated_pair, terminated},
     IResult,
 };
 
 pub fn parse(input: &str) -> IResult<&str, Vec<Instruction>> {
-    separated_list1(newline, command)(input)
+    all_consuming(terminated(
+        separated_list1(line_ending, command),
+        multispace0,
+    ))(input)
 }
 fn command(input: &str) -> IResult<&str, Instruction> {
-    preceded(tag("$ "), alt((cd, ls)))(input)
+    preceded(tag("$ "), alt((cd, ls, mkdir, rm, touch)))(input)
+}
+
+/// The rest of the line, which must not be empty. Names may hold any character, spaces included.
+fn name(input: &str) -> IResult<&str, &str> {
+    verify(not_line_ending, |name: &str| !name.is_empty())(input)
+}
+
+/// A path, split on `/`. Empty and `.` segments are dropped, so `./a//b` is `a/b`.
+fn target(input: &str) -> IResult<&str, Target> {
+    map(name, |path| {
+        let root = path.starts_with('/').then_some(DirName::Root);
+        let segments = path
+            .split('/')
+            .filter(|segment| !segment.is_empty() && *segment != ".")
+            .map(|segment| match segment {
+                ".." => DirName::Parent,
+                _ => DirName::Name(segment.to_string()),
+            });
+        Target(root.into_iter().chain(segments).collect())
+    })(input)
+}
+
+fn size(input: &str) -> IResult<&str, u64> {
+    map_res(digit1, |size: &str| size.parse::<u64>())(input)
 }
 
 fn cd(input: &str) -> IResult<&str, Instruction> {
-    dbg!("cd");
-    let (input, _) = tag("cd ")(input)?;
-    let (input, name) = take_until("\n")(input)?;
-    let target = match name {
-        "/" => DirName::Root,
-        ".." => DirName::Parent,
-        _ => DirName::Name(name.to_string()),
-    };
-    Ok((input, Instruction::ChangeDir(target)))
+    map(preceded(tag("cd "), target), Instruction::ChangeDir)(input)
+}
+
+fn mkdir(input: &str) -> IResult<&str, Instruction> {
+    map(preceded(tag("mkdir "), target), Instruction::MakeDir)(input)
+}
+
+fn rm(input: &str) -> IResult<&str, Instruction> {
+    map(preceded(tag("rm "), target), Instruction::Remove)(input)
+}
+
+/// `touch [size] path`. A leading number is only a size if a path follows it.
+fn touch(input: &str) -> IResult<&str, Instruction> {
+    map(
+        preceded(tag("touch "), pair(opt(terminated(size, tag(" "))), target)),
+        |(size, target)| Instruction::Touch(target, size),
+    )(input)
 }
 
 fn ls(input: &str) -> IResult<&str, Instruction> {
-    dbg!("ls");
     let (input, _) = tag("ls")(input)?;
-    let (input, _) = newline(input)?;
     let (input, list_output) = ls_output(input)?;
     Ok((input, Instruction::List(list_output)))
 }
 
+/// Each line of output after `ls`, which may be none at all
 fn ls_output(input: &str) -> IResult<&str, Vec<ListOutput>> {
-    separated_list1(newline, ls_output_line)(input)
-}
-
-fn filename(input: &str) -> IResult<&str, &str> {
-    is_a("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789.")(input)
+    many0(preceded(line_ending, ls_output_line))(input)
 }
 
 fn dir(input: &str) -> IResult<&str, ListOutput> {
-    let (input, _) = tag("dir ")(input)?;
-    let (input, name) = filename(input)?;
-    Ok((input, ListOutput::Dir(name.to_string())))
+    map(preceded(tag("dir "), name), |name| {
+        ListOutput::Dir(name.to_string())
+    })(input)
 }
 
 fn file(input: &str) -> IResult<&str, ListOutput> {
-    let (input, size) = map_res(digit1, |size: &str| size.parse::<u64>())(input)?;
-    let (input, _) = tag(" ")(input)?;
-    let (input, name) = filename(input)?;
-    Ok((input, ListOutput::File(size, name.to_string())))
+    map(separated_pair(size, tag(" "), name), |(size, name)| {
+        ListOutput::File(size, name.to_string())
+    })(input)
 }
 
 fn ls_output_line(input: &str) -> IResult<&str, ListOutput> {
     alt((dir, file))(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn awkward_transcript() {
+        let input = "$ cd /
+$ ls
+dir my-dir_1
+12 notes and such.txt
+$ cd my-dir_1
+$ ls
+$ cd ..
+$ cd my-dir_1";
+        let (_, got) = parse(input).expect("input must parse");
+        assert_eq!(
+            got,
+            vec![
+                Instruction::ChangeDir(DirName::Root.into()),
+                Instruction::List(vec![
+                    ListOutput::Dir("my-dir_1".to_string()),
+                    ListOutput::File(12, "notes and such.txt".to_string()),
+                ]),
+                Instruction::ChangeDir(DirName::Name("my-dir_1".to_string()).into()),
+                Instruction::List(vec![]),
+                Instruction::ChangeDir(DirName::Parent.into()),
+                Instruction::ChangeDir(DirName::Name("my-dir_1".to_string()).into()),
+            ]
+        );
+        assert!(parse(&format!("{input}\n")).is_ok());
+    }
+
+    #[test]
+    fn shell_commands() {
+        let input = "$ cd /x/y
+$ cd ../a/./b/
+$ mkdir new dir
+$ touch 12 notes.txt
+$ touch 12
+$ touch ./5 x
+$ rm /x";
+        let (_, got) = parse(input).expect("input must parse");
+        let name = |name: &str| DirName::Name(name.to_string());
+        assert_eq!(
+            got,
+            vec![
+                Instruction::ChangeDir(Target(vec![DirName::Root, name("x"), name("y")])),
+                Instruction::ChangeDir(Target(vec![DirName::Parent, name("a"), name("b")])),
+                Instruction::MakeDir(name("new dir").into()),
+                Instruction::Touch(name("notes.txt").into(), Some(12)),
+                Instruction::Touch(name("12").into(), None),
+                Instruction::Touch(name("5 x").into(), None),
+                Instruction::Remove(Target(vec![DirName::Root, name("x")])),
+            ]
+        );
+        let rendered = got
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(parse(&rendered).expect("rendering must parse").1, got);
+        assert_eq!(got[5].to_string(), "$ touch ./5 x");
+    }
+}
@@ -0,0 +1,480 @@
+use std::{
+    cmp::Ordering,
+    ops::{Bound, RangeBounds},
+    str::FromStr,
+};
+
+use nom::{
+    self,
+    branch::alt,
+    bytes::complete::{is_not, tag},
+    character::complete::{digit1, space0, space1},
+    combinator::{all_consuming, map, map_res, value},
+    multi::many0,
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
+
+use thiserror::Error;
+
+use crate::{DirId, FileSystem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    File,
+    Dir,
+}
+
+/// Something found by a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit {
+    pub kind: Kind,
+    pub path: String,
+    /// Recursive size for directories
+    pub size: u64,
+    /// How far below the root: `/a` is at depth 1
+    pub depth: usize,
+}
+
+/// A question to ask of a `FileSystem`, built up one condition at a time or parsed from text
+/// such as `dirs size<=100000` or `files name=*.txt depth<=2 largest=3`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    kind: Option<Kind>,
+    glob: Option<String>,
+    size: (Bound<u64>, Bound<u64>),
+    depth: (Bound<usize>, Bound<usize>),
+    largest: Option<usize>,
+}
+
+impl Query {
+    /// Everything, files and directories alike
+    pub fn all() -> Self {
+        Self {
+            kind: None,
+            glob: None,
+            size: (Bound::Unbounded, Bound::Unbounded),
+            depth: (Bound::Unbounded, Bound::Unbounded),
+            largest: None,
+        }
+    }
+    pub fn files() -> Self {
+        Self {
+            kind: Some(Kind::File),
+            ..Self::all()
+        }
+    }
+    pub fn dirs() -> Self {
+        Self {
+            kind: Some(Kind::Dir),
+            ..Self::all()
+        }
+    }
+    /// Only names matching a glob of `*` and `?` wildcards. A glob holding a `/` is matched
+    /// against the whole path instead.
+    pub fn matching(self, glob: &str) -> Self {
+        Self {
+            glob: Some(glob.to_string()),
+            ..self
+        }
+    }
+    /// Only sizes within `range`, as well as any size range already given
+    pub fn size(self, range: impl RangeBounds<u64>) -> Self {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        Self {
+            size: narrow(self.size, range),
+            ..self
+        }
+    }
+    /// Only depths within `range`, as well as any depth range already given
+    pub fn depth(self, range: impl RangeBounds<usize>) -> Self {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        Self {
+            depth: narrow(self.depth, range),
+            ..self
+        }
+    }
+    /// Only the `n` biggest hits
+    pub fn largest(self, n: usize) -> Self {
+        Self {
+            largest: Some(n),
+            ..self
+        }
+    }
+
+    fn accepts(&self, hit: &Hit) -> bool {
+        self.kind.is_none_or(|kind| kind == hit.kind)
+            && self.size.contains(&hit.size)
+            && self.depth.contains(&hit.depth)
+            && self.glob.as_ref().is_none_or(|glob| {
+                let name = if glob.contains('/') {
+                    hit.path.as_str()
+                } else {
+                    hit.path.rsplit('/').next().unwrap_or_default()
+                };
+                glob_matches(glob, name)
+            })
+    }
+}
+
+/// Whether `text` matches `glob`, where `*` matches any run of characters and `?` any one
+fn glob_matches(glob: &str, text: &str) -> bool {
+    let glob = glob.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut g, mut t) = (0, 0);
+    // where to pick up again if the last `*` has to swallow one more character
+    let mut backtrack = None;
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                backtrack = Some((g, t));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, swallowed)) => {
+                    g = star + 1;
+                    t = swallowed + 1;
+                    backtrack = Some((star, swallowed + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
+
+impl FileSystem {
+    /// Every file and directory matching the query, largest first
+    pub fn query(&self, query: &Query) -> Vec<Hit> {
+        let mut hits = self
+            .iter()
+            .flat_map(|(depth, id, dir)| {
+                let path = self.path(id);
+                let prefix = if id == DirId::ROOT { "" } else { &path };
+                let files = dir
+                    .files()
+                    .map(|(name, size)| Hit {
+                        kind: Kind::File,
+                        path: format!("{prefix}/{name}"),
+                        size,
+                        depth: depth + 1,
+                    })
+                    .collect::<Vec<_>>();
+                let dir = Hit {
+                    kind: Kind::Dir,
+                    path,
+                    size: dir.size(),
+                    depth,
+                };
+                std::iter::once(dir).chain(files)
+            })
+            .filter(|hit| query.accepts(hit))
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        if let Some(n) = query.largest {
+            hits.truncate(n);
+        }
+        hits
+    }
+
+    /// The directories to delete to free at least `target`, deleting as little as possible
+    /// overall. No chosen directory is inside another. Returns `Ok(None)` if even the root is
+    /// too small.
+    ///
+    /// A subset sum over the tree: walking depth first, a directory can only be added to totals
+    /// reached before entering it, so it never joins anything inside itself. Totals below
+    /// `target` are kept in a bitset with the directory that first reached each one, so the
+    /// cost is O(directories × target / 64) time and O(target × (4 + depth / 8)) bytes of
+    /// memory. Real directory trees reach so many different totals that nothing keyed by total
+    /// does better, so a target whose tables would pass `FREE_UP_MEMORY_LIMIT` is refused
+    /// rather than exhausting memory.
+    pub fn free_up(&self, target: u64) -> Result<Option<(u64, Vec<DirId>)>, FreeUpError> {
+        if self.root().size() < target {
+            return Ok(None);
+        }
+        if target == 0 {
+            return Ok(Some((0, vec![])));
+        }
+        // one bitset per directory on the current path plus `reached`, and a u32 per total
+        let depth = self
+            .iter()
+            .map(|(depth, _, _)| depth as u64)
+            .max()
+            .unwrap_or(0);
+        let bytes = (depth + 2)
+            .saturating_mul(target.div_ceil(64) * 8)
+            .saturating_add(target.saturating_mul(4));
+        if bytes > FREE_UP_MEMORY_LIMIT {
+            return Err(FreeUpError::TableTooLarge { target, bytes });
+        }
+        let target = target as usize;
+        let mut reached = vec![0u64; target.div_ceil(64)];
+        if let Some(first) = reached.first_mut() {
+            *first = 1;
+        }
+        let mut reached_by = vec![0u32; target];
+        // the best total at or past the target: (total, last directory, total before it)
+        let mut best: Option<(usize, DirId, usize)> = None;
+
+        let mut snapshots = vec![];
+        let mut stack = vec![(DirId::ROOT, false)];
+        while let Some((id, children_done)) = stack.pop() {
+            if !children_done {
+                snapshots.push(reached.clone());
+                stack.push((id, true));
+                stack.extend(self.get(id).children().map(|(_, child)| (child, false)));
+                continue;
+            }
+            let before = snapshots.pop().expect("every directory pushes a snapshot");
+            let size = self.get(id).size() as usize;
+            if let Some(rest) = first_set_bit(&before, target.saturating_sub(size)) {
+                if best.is_none_or(|(total, _, _)| rest + size < total) {
+                    best = Some((rest + size, id, rest));
+                }
+            }
+            // add this directory to every total from before it, for those still short
+            let (word_shift, bit_shift) = (size / 64, size % 64);
+            for word in (word_shift..reached.len()).rev() {
+                let src = word - word_shift;
+                let mut shifted = before[src] << bit_shift;
+                if bit_shift > 0 && src > 0 {
+                    shifted |= before[src - 1] >> (64 - bit_shift);
+                }
+                if word == reached.len() - 1 && !target.is_multiple_of(64) {
+                    shifted &= (1 << (target % 64)) - 1;
+                }
+                let mut new = shifted & !reached[word];
+                reached[word] |= new;
+                while new != 0 {
+                    reached_by[word * 64 + new.trailing_zeros() as usize] = id.0 as u32;
+                    new &= new - 1;
+                }
+            }
+        }
+
+        let (total, last, mut rest) = best.expect("the root alone frees enough");
+        let mut dirs = vec![last];
+        while rest > 0 {
+            let id = DirId(reached_by[rest] as usize);
+            dirs.push(id);
+            rest -= self.get(id).size() as usize;
+        }
+        Ok(Some((total as u64, dirs)))
+    }
+}
+
+/// The most memory `free_up` may use for its tables, in bytes: enough for the puzzle's 30MB
+/// update on trees up to about a hundred directories deep
+pub const FREE_UP_MEMORY_LIMIT: u64 = 512 << 20;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FreeUpError {
+    #[error("Freeing {target} bytes would need {bytes} bytes of tables, past the limit of {FREE_UP_MEMORY_LIMIT}")]
+    TableTooLarge { target: u64, bytes: u64 },
+}
+
+/// The first set bit at or after `from`
+fn first_set_bit(bits: &[u64], from: usize) -> Option<usize> {
+    let (start, offset) = (from / 64, from % 64);
+    let first = bits.get(start)? & (u64::MAX << offset);
+    std::iter::once(first)
+        .chain(bits[start + 1..].iter().copied())
+        .enumerate()
+        .find(|&(_, word)| word != 0)
+        .map(|(idx, word)| (start + idx) * 64 + word.trailing_zeros() as usize)
+}
+
+fn number<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(digit1, str::parse)(input)
+}
+
+/// A comparison such as `<=100`, as the bounds it allows
+fn comparison<T: FromStr + Copy>(input: &str) -> IResult<&str, (Bound<T>, Bound<T>)> {
+    alt((
+        map(preceded(tag("<="), number), |n| {
+            (Bound::Unbounded, Bound::Included(n))
+        }),
+        map(preceded(tag(">="), number), |n| {
+            (Bound::Included(n), Bound::Unbounded)
+        }),
+        map(preceded(tag("<"), number), |n| {
+            (Bound::Unbounded, Bound::Excluded(n))
+        }),
+        map(preceded(tag(">"), number), |n| {
+            (Bound::Excluded(n), Bound::Unbounded)
+        }),
+        map(preceded(tag("="), number), |n| {
+            (Bound::Included(n), Bound::Included(n))
+        }),
+    ))(input)
+}
+
+/// Narrow `current` to also satisfy `extra`, so `size>=10 size<=20` means both. Each end keeps
+/// the tighter of its two bounds, so `size<10 size<20` is still `size<10`.
+fn narrow<T: Copy + Ord>(
+    current: (Bound<T>, Bound<T>),
+    extra: (Bound<T>, Bound<T>),
+) -> (Bound<T>, Bound<T>) {
+    (
+        tighter(current.0, extra.0, Ordering::Greater),
+        tighter(current.1, extra.1, Ordering::Less),
+    )
+}
+
+/// Whichever of `a` and `b` admits less, where `inward` is the direction a tighter value lies
+/// in: `Greater` for lower bounds and `Less` for upper bounds. On equal values `Excluded` wins.
+fn tighter<T: Copy + Ord>(a: Bound<T>, b: Bound<T>, inward: Ordering) -> Bound<T> {
+    match (a, b) {
+        (Bound::Unbounded, other) | (other, Bound::Unbounded) => other,
+        (Bound::Excluded(x), Bound::Included(y)) | (Bound::Included(y), Bound::Excluded(x))
+            if x == y =>
+        {
+            Bound::Excluded(x)
+        }
+        (Bound::Included(x) | Bound::Excluded(x), Bound::Included(y) | Bound::Excluded(y)) => {
+            if y.cmp(&x) == inward {
+                b
+            } else {
+                a
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Clause {
+    Glob(String),
+    Size((Bound<u64>, Bound<u64>)),
+    Depth((Bound<usize>, Bound<usize>)),
+    Largest(usize),
+}
+
+fn clause(input: &str) -> IResult<&str, Clause> {
+    alt((
+        map(preceded(tag("name="), is_not(" \t")), |glob: &str| {
+            Clause::Glob(glob.to_string())
+        }),
+        map(preceded(tag("size"), comparison), Clause::Size),
+        map(preceded(tag("depth"), comparison), Clause::Depth),
+        map(preceded(tag("largest="), number), Clause::Largest),
+    ))(input)
+}
+
+fn query(input: &str) -> IResult<&str, Query> {
+    let (input, (query, clauses)) = all_consuming(delimited(
+        space0,
+        tuple((
+            alt((
+                value(Query::files(), tag("files")),
+                value(Query::dirs(), tag("dirs")),
+                value(Query::all(), tag("all")),
+            )),
+            many0(preceded(space1, clause)),
+        )),
+        space0,
+    ))(input)?;
+    let query = clauses
+        .into_iter()
+        .fold(query, |query, clause| match clause {
+            Clause::Glob(glob) => query.matching(&glob),
+            Clause::Size(size) => query.size(size),
+            Clause::Depth(depth) => query.depth(depth),
+            Clause::Largest(n) => query.largest(n),
+        });
+    Ok((input, query))
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (_, query) = query(s).map_err(|e| format!("Failed to parse query: {e}"))?;
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::filesystem, parser};
+
+    fn paths(hits: Vec<Hit>) -> Vec<String> {
+        hits.into_iter().map(|hit| hit.path).collect()
+    }
+
+    #[test]
+    fn queries() {
+        let filesystem = filesystem();
+        let small_dirs = filesystem.query(&Query::dirs().size(..=100000));
+        assert_eq!(paths(small_dirs), vec!["/a", "/a/e"]);
+
+        let query: Query = "files name=d.* largest=1".parse().unwrap();
+        assert_eq!(paths(filesystem.query(&query)), vec!["/d/d.log"]);
+
+        let query: Query = "all depth>=2 size<30000".parse().unwrap();
+        assert_eq!(query, Query::all().depth(2..).size(..30000));
+        assert_eq!(
+            paths(filesystem.query(&query)),
+            vec!["/a/f", "/a/g", "/a/e", "/a/e/i"]
+        );
+
+        // clauses on the same side keep the tighter bound, whichever order they come in
+        let query: Query = "dirs size<100000 size<=200000".parse().unwrap();
+        assert_eq!(query, Query::dirs().size(..100000));
+        // and the builder narrows the same way
+        let query: Query = "dirs size>=10 size<20".parse().unwrap();
+        assert_eq!(query, Query::dirs().size(10..).size(..20));
+        assert_eq!(query, Query::dirs().size(10..20));
+        let query: Query = "all depth>=1 depth>=2 depth>2 size<=584 size<584 size<=30000"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            query,
+            Query::all()
+                .depth((Bound::Excluded(2), Bound::Unbounded))
+                .size(..584)
+        );
+        assert_eq!(paths(filesystem.query(&query)), Vec::<String>::new());
+
+        assert!(glob_matches("*.l?t", "h.lst"));
+        assert!(glob_matches("/a/*", "/a/e/i"));
+        assert!(!glob_matches("*.txt", "c.dat"));
+    }
+
+    #[test]
+    fn free_up() {
+        let filesystem = filesystem();
+        let (total, dirs) = filesystem.free_up(8381165).unwrap().unwrap();
+        assert_eq!(total, 24933642);
+        assert_eq!(dirs, vec![filesystem.lookup("/d").unwrap()]);
+
+        // /d and /a/e together beat both /a with /d and the root
+        let (total, dirs) = filesystem.free_up(24933642 + 1).unwrap().unwrap();
+        let mut paths = dirs
+            .into_iter()
+            .map(|id| filesystem.path(id))
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(total, 24933642 + 584);
+        assert_eq!(paths, vec!["/a/e", "/d"]);
+
+        assert_eq!(filesystem.free_up(0), Ok(Some((0, vec![]))));
+        assert_eq!(filesystem.free_up(u64::MAX), Ok(None));
+
+        // a disk-sized target is refused up front instead of allocating a table that big
+        let (_, instructions) = parser::parse("$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\n50000000000 big")
+            .expect("input must parse");
+        let disk = FileSystem::try_from(instructions).unwrap();
+        assert!(matches!(
+            disk.free_up(40000000000),
+            Err(FreeUpError::TableTooLarge {
+                target: 40000000000,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt::Display;
+
+use crate::{DirId, FileSystem};
+
+/// Renders the tree the way the puzzle statement draws it, with directories and files sorted
+/// together by name:
+///
+/// ```text
+/// - / (dir)
+///   - a (dir)
+///     - f (file, size=29116)
+///   - b.txt (file, size=14848514)
+/// ```
+impl Display for FileSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut lines = vec![];
+        self.tree_lines(DirId::ROOT, 0, &mut lines);
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+enum Entry {
+    Dir(DirId),
+    File(u64),
+}
+
+impl FileSystem {
+    fn tree_lines(&self, id: DirId, depth: usize, lines: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        let dir = self.get(id);
+        let name = if id == DirId::ROOT { "/" } else { dir.name() };
+        lines.push(format!("{indent}- {name} (dir)"));
+
+        let mut entries = dir
+            .children()
+            .map(|(name, id)| (name, Entry::Dir(id)))
+            .chain(dir.files().map(|(name, size)| (name, Entry::File(size))))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|&(name, _)| name);
+        for (name, entry) in entries {
+            match entry {
+                Entry::Dir(child) => self.tree_lines(child, depth + 1, lines),
+                Entry::File(size) => lines.push(format!("{indent}  - {name} (file, size={size})")),
+            }
+        }
+    }
+
+    /// A `du -h` style listing of every directory's recursive size, largest first
+    pub fn disk_usage(&self) -> DiskUsage<'_> {
+        DiskUsage(self)
+    }
+}
+
+pub struct DiskUsage<'a>(&'a FileSystem);
+
+impl Display for DiskUsage<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut sizes = self.0.sizes().into_iter().collect::<Vec<_>>();
+        sizes.sort_by(|(path_a, size_a), (path_b, size_b)| {
+            size_b.cmp(size_a).then_with(|| path_a.cmp(path_b))
+        });
+        let lines = sizes
+            .into_iter()
+            .map(|(path, size)| format!("{}\t{path}", human_size(size)))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Format a size in bytes as `du -h` does: powers of 1024, rounded up, with one decimal place
+/// below 10.
+pub fn human_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+    if size < 1024 {
+        return size.to_string();
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value.ceil() >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value < 10.0 {
+        let tenths = (value * 10.0).ceil();
+        if tenths < 100.0 {
+            return format!("{:.1}{}", tenths / 10.0, UNITS[unit]);
+        }
+    }
+    format!("{}{}", value.ceil(), UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::filesystem;
+
+    #[test]
+    fn tree() {
+        let expected = "- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)";
+        assert_eq!(filesystem().to_string(), expected);
+    }
+
+    #[test]
+    fn disk_usage() {
+        let expected = "47M\t/
+24M\t/d
+93K\t/a
+584\t/a/e";
+        assert_eq!(filesystem().disk_usage().to_string(), expected);
+        assert_eq!(super::human_size(1024), "1.0K");
+        assert_eq!(super::human_size(1536), "1.5K");
+        assert_eq!(super::human_size(10 * 1024 + 1), "11K");
+    }
+}
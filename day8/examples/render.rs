@@ -0,0 +1,31 @@
+//! Draw the puzzle input's visibility map and scenic heatmap, as text on stdout and as PGM
+//! images in the directory given as the first argument (the current directory by default).
+//!
+//! `cargo run -p day8 --example render -- /tmp`
+
+use std::{env, fs, path::PathBuf};
+
+use day8::Forest;
+
+const INPUT: &str = include_str!("../src/input.txt");
+/// Pixels per tree along each side of the images
+const SCALE: usize = 4;
+
+fn main() -> std::io::Result<()> {
+    let dir = PathBuf::from(env::args().nth(1).unwrap_or_else(|| ".".to_string()));
+    let rows = INPUT
+        .lines()
+        .map(|line| line.bytes().map(|b| b - b'0').collect())
+        .collect();
+    let forest = Forest::new(rows).expect("input must be rectangular");
+
+    let heatmap = forest.scenic_heatmap();
+    println!("{}\n\n{heatmap}", forest.visibility_map());
+    if let Some(((x, y), score)) = forest.best_location() {
+        println!("\nbest location: ({x}, {y}) with a scenic score of {score}");
+    }
+
+    fs::write(dir.join("visibility.pgm"), forest.visibility_pgm(SCALE))?;
+    fs::write(dir.join("heatmap.pgm"), heatmap.pgm(SCALE))?;
+    Ok(())
+}
@@ -0,0 +1,290 @@
+use std::ops::{Index, IndexMut};
+
+use thiserror::Error;
+
+/// A position in a grid as `(x, y)`, with `(0, 0)` at the top left
+pub type Coords = (usize, usize);
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum GridError {
+    #[error("Row {row} has {found} cells but the first row has {expected}")]
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// The eight compass directions, with up towards row 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    /// The four directions along rows and columns, clockwise from up
+    pub const ORTHOGONAL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+    /// All eight directions, clockwise from up
+    pub const ALL: [Self; 8] = [
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+    ];
+
+    /// How far one step moves, as `(dx, dy)`
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Self::Up => (0, -1),
+            Self::UpRight => (1, -1),
+            Self::Right => (1, 0),
+            Self::DownRight => (1, 1),
+            Self::Down => (0, 1),
+            Self::DownLeft => (-1, 1),
+            Self::Left => (-1, 0),
+            Self::UpLeft => (-1, -1),
+        }
+    }
+}
+
+/// A dense rectangular grid, stored row by row in a single `Vec`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Build a grid from its rows, which must all be the same length
+    pub fn new(rows: Vec<Vec<T>>) -> Result<Self, GridError> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        let mut cells = Vec::with_capacity(width * height);
+        for (row, cells_in_row) in rows.into_iter().enumerate() {
+            if cells_in_row.len() != width {
+                return Err(GridError::RaggedRow {
+                    row,
+                    expected: width,
+                    found: cells_in_row.len(),
+                });
+            }
+            cells.extend(cells_in_row);
+        }
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Build a grid by calling `f` with the coordinates of each cell
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Coords) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut f)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Coords) -> bool {
+        x < self.width && y < self.height
+    }
+
+    fn offset_of(&self, coords: Coords) -> Option<usize> {
+        self.contains(coords)
+            .then(|| coords.1 * self.width + coords.0)
+    }
+
+    pub fn get(&self, coords: Coords) -> Option<&T> {
+        self.offset_of(coords).map(|offset| &self.cells[offset])
+    }
+
+    pub fn get_mut(&mut self, coords: Coords) -> Option<&mut T> {
+        self.offset_of(coords).map(|offset| &mut self.cells[offset])
+    }
+
+    /// The cell one step from `coords`, if that is still inside the grid
+    pub fn step(&self, (x, y): Coords, direction: Direction) -> Option<Coords> {
+        let (dx, dy) = direction.offset();
+        let next = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(next).then_some(next)
+    }
+
+    /// Every position, row by row from the top left
+    pub fn coords(&self) -> impl Iterator<Item = Coords> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell with its position, row by row from the top left
+    pub fn iter(&self) -> impl Iterator<Item = (Coords, &T)> {
+        self.coords().zip(&self.cells)
+    }
+
+    /// The cells of row `y`, left to right. Empty if there is no such row.
+    pub fn row(&self, y: usize) -> impl DoubleEndedIterator<Item = &T> {
+        let row = if y < self.height {
+            &self.cells[y * self.width..(y + 1) * self.width]
+        } else {
+            &[]
+        };
+        row.iter()
+    }
+
+    /// The cells of column `x`, top to bottom. Empty if there is no such column.
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        let column = if x < self.width {
+            &self.cells[x..]
+        } else {
+            &[]
+        };
+        column.iter().step_by(self.width.max(1))
+    }
+
+    /// Every row, top to bottom. A grid with no columns still has `height` rows, all empty.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    /// The cells met walking from `coords` in a straight line to the edge, not counting the
+    /// starting cell itself
+    pub fn ray(&self, coords: Coords, direction: Direction) -> impl Iterator<Item = (Coords, &T)> {
+        let start = self.contains(coords).then_some(coords);
+        std::iter::successors(start, move |&coords| self.step(coords, direction))
+            .skip(1)
+            .map(|coords| (coords, &self[coords]))
+    }
+
+    /// The up to four cells sharing an edge with `coords`
+    pub fn neighbors4(&self, coords: Coords) -> impl Iterator<Item = Coords> + '_ {
+        Direction::ORTHOGONAL
+            .into_iter()
+            .filter_map(move |direction| self.step(coords, direction))
+    }
+
+    /// The up to eight cells sharing an edge or a corner with `coords`
+    pub fn neighbors8(&self, coords: Coords) -> impl Iterator<Item = Coords> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(coords, direction))
+    }
+
+    /// A grid of the same shape with `f` applied to every cell
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Coords> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coords: Coords) -> &Self::Output {
+        self.get(coords).unwrap_or_else(|| {
+            panic!(
+                "{coords:?} is outside the {}x{} grid",
+                self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<Coords> for Grid<T> {
+    fn index_mut(&mut self, coords: Coords) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(coords)
+            .unwrap_or_else(|| panic!("{coords:?} is outside the {width}x{height} grid"))
+    }
+}
+
+impl<T> TryFrom<Vec<Vec<T>>> for Grid<T> {
+    type Error = GridError;
+
+    fn try_from(rows: Vec<Vec<T>>) -> Result<Self, Self::Error> {
+        Self::new(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<u8> {
+        Grid::new(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap()
+    }
+
+    #[test]
+    fn shape() {
+        let grid = grid();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get((0, 2)), None);
+        assert_eq!(
+            Grid::new(vec![vec![1, 2], vec![3]]),
+            Err(GridError::RaggedRow {
+                row: 1,
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn lines() {
+        let grid = grid();
+        assert_eq!(grid.row(1).copied().collect::<Vec<_>>(), vec![4, 5, 6]);
+        assert_eq!(
+            grid.column(2).rev().copied().collect::<Vec<_>>(),
+            vec![6, 3]
+        );
+        assert_eq!(grid.row(2).count(), 0);
+        assert_eq!(grid.rows().count(), 2);
+        let narrow = Grid::<u8>::new(vec![vec![], vec![]]).unwrap();
+        assert_eq!(narrow.rows().collect::<Vec<_>>(), vec![&[]; 2]);
+        let ray = grid
+            .ray((0, 0), Direction::Right)
+            .map(|(_, &cell)| cell)
+            .collect::<Vec<_>>();
+        assert_eq!(ray, vec![2, 3]);
+        assert_eq!(grid.ray((0, 0), Direction::DownRight).count(), 1);
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = grid();
+        assert_eq!(
+            grid.neighbors4((0, 0)).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbors8((1, 0)).count(), 5);
+        assert_eq!(grid.neighbors8((1, 1)).count(), 5);
+    }
+}
@@ -1,180 +1,140 @@
-pub struct Forest(Vec<Vec<u8>>);
+pub mod grid;
+pub mod render;
+pub mod sight;
 
-trait CoordinateWayfinding
-where
-    Self: Sized,
-{
-    fn up(&self) -> Option<Self>;
-    fn right(&self) -> Option<Self>;
-    fn left(&self) -> Option<Self>;
-    fn down(&self) -> Option<Self>;
-}
-
-type Coords = (usize, usize);
-impl CoordinateWayfinding for Coords {
-    fn up(&self) -> Option<Self> {
-        let (x, y) = *self;
-        let y = y.checked_sub(1)?;
-        Some((x, y))
-    }
-
-    fn right(&self) -> Option<Self> {
-        let (x, y) = *self;
-        let x = x.checked_add(1)?;
-        Some((x, y))
-    }
-
-    fn left(&self) -> Option<Self> {
-        let (x, y) = *self;
-        let x = x.checked_sub(1)?;
-        Some((x, y))
-    }
+use grid::{Coords, Direction, Grid, GridError};
 
-    fn down(&self) -> Option<Self> {
-        let (x, y) = *self;
-        let y = y.checked_add(1)?;
-        Some((x, y))
-    }
-}
+#[derive(Debug, Clone)]
+pub struct Forest(Grid<u8>);
 
 impl Forest {
-    pub fn new(data: Vec<Vec<u8>>) -> Self {
-        Self(data)
+    /// A forest from rows of tree heights, which must all be the same length
+    pub fn new(data: Vec<Vec<u8>>) -> Result<Self, GridError> {
+        Grid::new(data).map(Self)
+    }
+    pub fn trees(&self) -> &Grid<u8> {
+        &self.0
     }
     pub fn iter_coords(&self) -> impl Iterator<Item = Coords> {
-        self.0
-            .iter()
-            .enumerate()
-            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, _)| (x, y)))
-            .collect::<Vec<_>>()
-            .into_iter()
+        self.0.coords()
     }
     pub fn in_forest(&self, coords: Coords) -> bool {
-        let height = self.0.len();
-        let width = self.0.get(0).unwrap().len();
-        let (x, y) = coords;
-        y <= height && x <= width
+        self.0.contains(coords)
     }
-    fn up_from(&self, coords: Coords) -> Vec<&u8> {
-        self.0
-            .iter()
-            .enumerate()
-            .flat_map(|(y, row)| {
-                row.iter().enumerate().filter_map(move |(x, height)| {
-                    if x == coords.0 && y < coords.1 {
-                        Some(height)
-                    } else {
-                        None
-                    }
-                })
-            })
-            .rev()
-            .collect()
+
+    /// The scenic score of one tree, found by walking out from it. Use `scenic_scores` to score
+    /// the whole forest.
+    pub fn scenic_score(&self, coords: Coords) -> Option<usize> {
+        self.sightlines(coords)
+            .map(|sightlines| sightlines.scenic_score())
     }
-    fn down_from(&self, coords: Coords) -> Vec<&u8> {
-        self.0
-            .iter()
-            .enumerate()
-            .flat_map(|(y, row)| {
-                row.iter().enumerate().filter_map(move |(x, height)| {
-                    if x == coords.0 && y > coords.1 {
-                        Some(height)
-                    } else {
-                        None
-                    }
-                })
-            })
-            .collect()
+
+    /// Whether one tree can be seen from outside the forest, found by walking out from it. Use
+    /// `visibility` to check the whole forest.
+    pub fn is_tree_visible(&self, coords: Coords) -> Option<bool> {
+        self.sightlines(coords)
+            .map(|sightlines| sightlines.is_visible())
     }
-    fn left_from(&self, coords: Coords) -> Vec<&u8> {
-        self.0
-            .iter()
-            .enumerate()
-            .flat_map(|(y, row)| {
-                row.iter().enumerate().filter_map(move |(x, height)| {
-                    if y == coords.1 && x < coords.0 {
-                        Some(height)
-                    } else {
-                        None
-                    }
-                })
+
+    /// Every line a sweep has to follow: each row and each column, in both directions
+    fn sight_lines(&self) -> impl Iterator<Item = Vec<Coords>> + '_ {
+        let (width, height) = (self.0.width(), self.0.height());
+        let starts = (0..height)
+            .flat_map(move |y| {
+                [
+                    ((0, y), Direction::Right),
+                    ((width.saturating_sub(1), y), Direction::Left),
+                ]
             })
-            .rev()
-            .collect()
-    }
-    fn right_from(&self, coords: Coords) -> Vec<&u8> {
-        self.0
-            .iter()
-            .enumerate()
-            .flat_map(|(y, row)| {
-                row.iter().enumerate().filter_map(move |(x, height)| {
-                    if y == coords.1 && x > coords.0 {
-                        Some(height)
-                    } else {
-                        None
-                    }
-                })
+            .chain((0..width).flat_map(move |x| {
+                [
+                    ((x, 0), Direction::Down),
+                    ((x, height.saturating_sub(1)), Direction::Up),
+                ]
+            }));
+        starts
+            .filter(|&(start, _)| self.0.contains(start))
+            .map(|(start, direction)| {
+                std::iter::once(start)
+                    .chain(self.0.ray(start, direction).map(|(coords, _)| coords))
+                    .collect()
             })
-            .collect()
     }
 
-    pub fn scenic_score(&self, coords: Coords) -> Option<usize> {
-        let (x, y) = coords;
-        let tree_height = self.0.get(y)?.get(x)?;
-        Some(
-            [
-                self.up_from(coords),
-                self.right_from(coords),
-                self.down_from(coords),
-                self.left_from(coords),
-            ]
-            .map(|path| {
-                path.into_iter()
-                    .fold((0, false), |(count, should_stop), neighbor_height| {
-                        if should_stop {
-                            (count, should_stop)
-                        } else {
-                            (count + 1, should_stop || neighbor_height >= tree_height)
-                        }
-                    })
-            })
-            .map(|(count, _)| count)
-            .into_iter()
-            .product(),
-        )
+    /// Whether each tree can be seen from outside the forest.
+    ///
+    /// Sweeps each row and column from both ends, keeping the tallest tree seen so far: a tree
+    /// is visible from that end exactly when it is taller. O(1) per tree per direction.
+    pub fn visibility(&self) -> Grid<bool> {
+        let mut visible = self.0.map(|_| false);
+        for line in self.sight_lines() {
+            let mut tallest = None;
+            for coords in line {
+                let height = self.0[coords];
+                if tallest.is_none_or(|tallest| height > tallest) {
+                    visible[coords] = true;
+                    tallest = Some(height);
+                }
+            }
+        }
+        visible
     }
 
-    pub fn is_tree_visible(&self, coords: Coords) -> Option<bool> {
-        let tree_height = self.0.get(coords.1)?.get(coords.0)?;
-        Some(
-            [
-                self.left_from(coords),
-                self.up_from(coords),
-                self.right_from(coords),
-                self.down_from(coords),
-            ]
-            .into_iter()
-            .any(|direction| {
-                direction
-                    .into_iter()
-                    .all(|neighbor_height| neighbor_height < tree_height)
-            }),
-        )
+    /// The scenic score of each tree.
+    ///
+    /// Sweeps each row and column from both ends with a stack of the trees that could still
+    /// block the view, strictly decreasing in height. Each new tree pops every shorter tree off,
+    /// since it hides them from everything further along, and then sees as far as the tree left
+    /// on top, or to the edge. Every tree is pushed and popped once per direction, so the whole
+    /// forest is scored in linear time.
+    pub fn scenic_scores(&self) -> Grid<usize> {
+        let mut scores = self.0.map(|_| 1);
+        for line in self.sight_lines() {
+            let mut blockers: Vec<(usize, u8)> = vec![];
+            for (position, coords) in line.into_iter().enumerate() {
+                let height = self.0[coords];
+                while blockers
+                    .last()
+                    .is_some_and(|&(_, blocker)| blocker < height)
+                {
+                    blockers.pop();
+                }
+                let distance = blockers.last().map_or(position, |&(blocker_position, _)| {
+                    position - blocker_position
+                });
+                scores[coords] *= distance;
+                blockers.push((position, height));
+            }
+        }
+        scores
     }
 }
 
+/// The puzzle's example, shared by every module's tests
 #[cfg(test)]
-mod tests {
-    use super::*;
-    #[test]
-    fn is_tree_visible_count() {
-        let input = Forest(vec![
+mod fixtures {
+    use crate::Forest;
+
+    pub fn forest() -> Forest {
+        Forest::new(vec![
             vec![3, 0, 3, 7, 3],
             vec![2, 5, 5, 1, 2],
             vec![6, 5, 3, 3, 2],
             vec![3, 3, 5, 4, 9],
             vec![3, 5, 3, 9, 0],
-        ]);
+        ])
+        .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::forest;
+
+    #[test]
+    fn is_tree_visible_count() {
+        let input = forest();
         let count = input
             .iter_coords()
             .filter(|&coords| input.is_tree_visible(coords).unwrap_or(false))
@@ -182,18 +142,36 @@ mod tests {
         assert_eq!(count, 21);
     }
 
+    #[test]
+    fn out_of_bounds() {
+        let input = forest();
+        assert!(input.in_forest((4, 4)));
+        assert!(!input.in_forest((5, 4)));
+        assert_eq!(input.is_tree_visible((5, 0)), None);
+        let empty = Forest::new(vec![vec![], vec![]]).unwrap();
+        assert_eq!(empty.visibility().iter().count(), 0);
+    }
+
     #[test]
     fn scenic_score() {
-        let input = Forest(vec![
-            vec![3, 0, 3, 7, 3],
-            vec![2, 5, 5, 1, 2],
-            vec![6, 5, 3, 3, 2],
-            vec![3, 3, 5, 4, 9],
-            vec![3, 5, 3, 9, 0],
-        ]);
+        let input = forest();
         let coords = (2, 1);
         assert_eq!(input.scenic_score(coords), Some(4));
         let coords = (2, 3);
         assert_eq!(input.scenic_score(coords), Some(8));
     }
+
+    #[test]
+    fn sweeps_match_single_trees() {
+        let input = forest();
+        let visibility = input.visibility();
+        let scores = input.scenic_scores();
+        assert_eq!(visibility.iter().filter(|(_, &v)| v).count(), 21);
+        assert_eq!(scores.iter().map(|(_, &score)| score).max(), Some(8));
+        assert_eq!(scores[(2, 3)], 8);
+        for coords in input.iter_coords() {
+            assert_eq!(input.is_tree_visible(coords), Some(visibility[coords]));
+            assert_eq!(input.scenic_score(coords), Some(scores[coords]));
+        }
+    }
 }
@@ -5,17 +5,15 @@ use day8::Forest;
 const INPUT: &str = include_str!("input.txt");
 
 fn solve_part1(input: Input) -> usize {
-    let forest = Forest::new(input.0);
-    forest
-        .iter_coords()
-        .filter(|&coords| forest.is_tree_visible(coords).unwrap_or(false))
-        .count()
+    let Input(forest) = input;
+    forest.visibility().iter().filter(|(_, &v)| v).count()
 }
 fn solve_part2(input: Input) -> usize {
-    let forest = Forest::new(input.0);
+    let Input(forest) = input;
     forest
-        .iter_coords()
-        .filter_map(|coords| forest.scenic_score(coords))
+        .scenic_scores()
+        .iter()
+        .map(|(_, &score)| score)
         .max()
         .unwrap()
 }
@@ -29,7 +27,7 @@ fn main() {
 }
 
 #[derive(Clone, Debug)]
-struct Input(Vec<Vec<u8>>);
+struct Input(Forest);
 impl FromStr for Input {
     type Err = String;
 
@@ -43,6 +41,6 @@ impl FromStr for Input {
             })
             .collect::<Option<Vec<Vec<u8>>>>()
             .ok_or_else(|| String::from("Failed to parse input"))?;
-        Ok(Self(data))
+        Forest::new(data).map(Self).map_err(|e| e.to_string())
     }
 }
@@ -0,0 +1,185 @@
+use std::fmt::{Display, Write};
+
+use crate::{
+    grid::{Coords, Grid},
+    Forest,
+};
+
+/// Shades for the text heatmap, from least to most scenic
+const SHADES: &[u8] = b" .:-=+*%#@";
+/// How the best location shows up in the text heatmap
+const BEST: char = 'X';
+/// The brightest grey a heatmap score gets in a PGM image, leaving pure white for the best
+/// location
+const HEATMAP_MAX_GREY: u8 = 191;
+
+impl Forest {
+    /// The most scenic tree and its score. Ties go to the first in reading order.
+    pub fn best_location(&self) -> Option<(Coords, usize)> {
+        self.scenic_scores()
+            .iter()
+            .map(|(coords, &score)| (coords, score))
+            .reduce(|best, next| if next.1 > best.1 { next } else { best })
+    }
+
+    /// A map of the forest showing the height of every tree visible from outside, and `.` for
+    /// every hidden tree
+    pub fn visibility_map(&self) -> VisibilityMap {
+        VisibilityMap {
+            trees: self.trees().clone(),
+            visibility: self.visibility(),
+        }
+    }
+
+    /// A map of scenic scores, shaded from ` ` for the least scenic trees to `@` for the most,
+    /// with the best location marked `X`. Shades follow the logarithm of the score, since a few
+    /// trees score far above the rest.
+    pub fn scenic_heatmap(&self) -> Heatmap {
+        Heatmap::new(self.scenic_scores())
+    }
+
+    /// The visibility map as a plain PGM image, with visible trees white and hidden trees black.
+    /// Each tree is drawn as a `scale` by `scale` square.
+    pub fn visibility_pgm(&self, scale: usize) -> String {
+        pgm(&self.visibility().map(|&visible| visible as u8), 1, scale)
+    }
+}
+
+pub struct VisibilityMap {
+    trees: Grid<u8>,
+    visibility: Grid<bool>,
+}
+
+impl Display for VisibilityMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.trees.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for (x, height) in row.iter().enumerate() {
+                match self.visibility[(x, y)] {
+                    true => write!(f, "{height}")?,
+                    false => f.write_char('.')?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct Heatmap {
+    /// Each tree's brightness, from 0 for a score of 0 up to 1 for the best score
+    levels: Grid<f64>,
+    best: Option<Coords>,
+}
+
+impl Heatmap {
+    fn new(scores: Grid<usize>) -> Self {
+        let best = scores
+            .iter()
+            .reduce(|best, next| if next.1 > best.1 { next } else { best })
+            .map(|(coords, &score)| (coords, score));
+        let top = best.map_or(0, |(_, score)| score);
+        let levels = scores.map(|&score| match top {
+            0 => 0.0,
+            _ => (score as f64).ln_1p() / (top as f64).ln_1p(),
+        });
+        Self {
+            levels,
+            best: best.map(|(coords, _)| coords),
+        }
+    }
+
+    /// The heatmap as a plain PGM image, in greys up to `HEATMAP_MAX_GREY` with the best
+    /// location in pure white. Each tree is drawn as a `scale` by `scale` square.
+    pub fn pgm(&self, scale: usize) -> String {
+        let mut greys = self
+            .levels
+            .map(|level| (level * HEATMAP_MAX_GREY as f64).round() as u8);
+        if let Some(best) = self.best {
+            greys[best] = u8::MAX;
+        }
+        pgm(&greys, u8::MAX, scale)
+    }
+}
+
+impl Display for Heatmap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.levels.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for (x, level) in row.iter().enumerate() {
+                if self.best == Some((x, y)) {
+                    f.write_char(BEST)?;
+                } else {
+                    let shade = (level * (SHADES.len() - 1) as f64).round() as usize;
+                    f.write_char(SHADES[shade] as char)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encode a grid of grey levels from 0 to `max_grey` as a plain (`P2`) PGM image, drawing each
+/// cell as a `scale` by `scale` square
+pub fn pgm(greys: &Grid<u8>, max_grey: u8, scale: usize) -> String {
+    let scale = scale.max(1);
+    let mut image = format!(
+        "P2\n{} {}\n{max_grey}\n",
+        greys.width() * scale,
+        greys.height() * scale
+    );
+    for row in greys.rows() {
+        let line = row
+            .iter()
+            .flat_map(|grey| std::iter::repeat_n(grey.to_string(), scale))
+            .collect::<Vec<_>>()
+            .join(" ");
+        for _ in 0..scale {
+            image.push_str(&line);
+            image.push('\n');
+        }
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fixtures::forest, Forest};
+
+    #[test]
+    fn visibility() {
+        let forest = forest();
+        let expected = "30373
+255.2
+65.32
+3.5.9
+35390";
+        assert_eq!(forest.visibility_map().to_string(), expected);
+        // a forest of empty rows still has its rows, each blank
+        let empty = Forest::new(vec![vec![], vec![]]).unwrap();
+        assert_eq!(empty.visibility_map().to_string(), "\n");
+        let pgm = forest.visibility_pgm(2);
+        let mut lines = pgm.lines();
+        assert_eq!(lines.next(), Some("P2"));
+        assert_eq!(lines.next(), Some("10 10"));
+        assert_eq!(lines.next(), Some("1"));
+        assert_eq!(lines.nth(2), Some("1 1 1 1 1 1 0 0 1 1"));
+        assert_eq!(lines.count(), 7);
+    }
+
+    #[test]
+    fn heatmap() {
+        let forest = forest();
+        assert_eq!(forest.best_location(), Some(((2, 3), 8)));
+        let heatmap = forest.scenic_heatmap();
+        let rendered = heatmap.to_string();
+        assert_eq!(rendered.lines().nth(3).unwrap().chars().nth(2), Some('X'));
+        // the edges of the forest all score 0
+        assert!(rendered.lines().all(|line| line.starts_with(' ')));
+        let pgm = heatmap.pgm(1);
+        assert_eq!(pgm.lines().nth(3 + 3), Some("0 60 255 121 0"));
+    }
+}
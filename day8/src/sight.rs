@@ -0,0 +1,171 @@
+use crate::{
+    grid::{Coords, Direction},
+    Forest,
+};
+
+/// What can be seen looking one way from a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct View {
+    pub direction: Direction,
+    /// How many trees can be seen, counting the tree that blocks the view
+    pub distance: usize,
+    /// The first tree at least as tall as the observer, which cuts the view short. `None` when
+    /// the view reaches the edge of the forest.
+    pub blocker: Option<Coords>,
+}
+
+impl View {
+    pub fn reaches_edge(&self) -> bool {
+        self.blocker.is_none()
+    }
+}
+
+/// The views in all four directions from one cell, at some height
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sightlines {
+    pub coords: Coords,
+    /// The height looked from, which is the tree's own unless an observer stands there instead
+    pub height: u8,
+    /// One view per direction, in the order of `Direction::ORTHOGONAL`
+    pub views: [View; 4],
+}
+
+impl Sightlines {
+    /// The view in one of the four orthogonal directions
+    pub fn view(&self, direction: Direction) -> Option<&View> {
+        self.views.iter().find(|view| view.direction == direction)
+    }
+
+    /// The edges this cell can be seen from, named by the direction they lie in. A tree can be
+    /// seen from an edge exactly when it can see that edge.
+    pub fn visible_from(&self) -> impl Iterator<Item = Direction> + '_ {
+        self.views
+            .iter()
+            .filter(|view| view.reaches_edge())
+            .map(|view| view.direction)
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible_from().next().is_some()
+    }
+
+    pub fn scenic_score(&self) -> usize {
+        self.views.iter().map(|view| view.distance).product()
+    }
+}
+
+impl Forest {
+    /// Look from `coords` in `direction` with eyes at `height`, seeing over every tree shorter
+    /// than that
+    pub fn look(&self, coords: Coords, direction: Direction, height: u8) -> Option<View> {
+        if !self.in_forest(coords) {
+            return None;
+        }
+        let mut distance = 0;
+        for (neighbor, &neighbor_height) in self.trees().ray(coords, direction) {
+            distance += 1;
+            if neighbor_height >= height {
+                return Some(View {
+                    direction,
+                    distance,
+                    blocker: Some(neighbor),
+                });
+            }
+        }
+        Some(View {
+            direction,
+            distance,
+            blocker: None,
+        })
+    }
+
+    /// The views from an observer with eyes at `height`, standing at `coords` in place of the
+    /// tree there
+    pub fn observe(&self, coords: Coords, height: u8) -> Option<Sightlines> {
+        let views = Direction::ORTHOGONAL.map(|direction| self.look(coords, direction, height));
+        Some(Sightlines {
+            coords,
+            height,
+            views: [views[0]?, views[1]?, views[2]?, views[3]?],
+        })
+    }
+
+    /// The views from the top of the tree at `coords`
+    pub fn sightlines(&self, coords: Coords) -> Option<Sightlines> {
+        self.observe(coords, *self.trees().get(coords)?)
+    }
+
+    /// Every tree an observer with eyes at `height` standing at `coords` can see, direction by
+    /// direction, nearest first
+    pub fn seen_from(&self, coords: Coords, height: u8) -> Option<Vec<Coords>> {
+        let sightlines = self.observe(coords, height)?;
+        Some(
+            sightlines
+                .views
+                .iter()
+                .flat_map(|view| {
+                    self.trees()
+                        .ray(coords, view.direction)
+                        .take(view.distance)
+                        .map(|(coords, _)| coords)
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::forest,
+        grid::Direction::{Down, Left, Right, Up},
+    };
+
+    #[test]
+    fn sightlines() {
+        let forest = forest();
+        let sightlines = forest.sightlines((2, 3)).unwrap();
+        let views = sightlines
+            .views
+            .iter()
+            .map(|view| (view.direction, view.distance, view.blocker))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            views,
+            vec![
+                (Up, 2, Some((2, 1))),
+                (Right, 2, Some((4, 3))),
+                (Down, 1, None),
+                (Left, 2, None),
+            ]
+        );
+        assert_eq!(
+            sightlines.visible_from().collect::<Vec<_>>(),
+            vec![Down, Left]
+        );
+        assert_eq!(sightlines.scenic_score(), 8);
+
+        let hidden = forest.sightlines((2, 2)).unwrap();
+        assert!(!hidden.is_visible());
+        assert_eq!(hidden.view(Left).unwrap().blocker, Some((1, 2)));
+        assert_eq!(forest.sightlines((5, 0)), None);
+    }
+
+    #[test]
+    fn observer() {
+        let forest = forest();
+        // tall enough to see over everything but the 9 below
+        let observer = forest.observe((3, 2), 9).unwrap();
+        assert_eq!(
+            observer.visible_from().collect::<Vec<_>>(),
+            vec![Up, Right, Left]
+        );
+        assert_eq!(observer.view(Down).unwrap().blocker, Some((3, 4)));
+        assert_eq!(forest.seen_from((3, 2), 9).unwrap().len(), 8);
+        // too short to see past the neighbors
+        assert_eq!(
+            forest.seen_from((3, 2), 0).unwrap(),
+            vec![(3, 1), (4, 2), (3, 3), (2, 2)]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use crate::Vector;
+
+/// How the distance between two knots is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjacency {
+    /// Diagonal neighbours count as one apart, and knots catch up diagonally
+    Chebyshev,
+    /// Diagonal neighbours count as two apart, and knots only move along rows and columns
+    Manhattan,
+}
+
+/// When a knot has fallen too far behind the one in front of it, and how it catches up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FollowRule {
+    pub adjacency: Adjacency,
+    /// How far apart two knots may get before the follower has to move
+    pub slack: i32,
+}
+
+impl FollowRule {
+    /// The rule from the puzzle: a knot moves once it stops touching its leader, diagonally if
+    /// need be
+    pub const PUZZLE: Self = Self {
+        adjacency: Adjacency::Chebyshev,
+        slack: 1,
+    };
+
+    pub fn new(adjacency: Adjacency, slack: i32) -> Self {
+        Self { adjacency, slack }
+    }
+
+    fn distance(&self, gap: Vector) -> i32 {
+        match self.adjacency {
+            Adjacency::Chebyshev => gap.chebyshev_length(),
+            Adjacency::Manhattan => gap.manhattan_length(),
+        }
+    }
+
+    /// The single step a follower takes to close `gap`, the leader's position relative to it
+    fn step(&self, gap: Vector) -> Vector {
+        match self.adjacency {
+            Adjacency::Chebyshev => gap.signum(),
+            // close the wider gap first, preferring columns on a tie
+            Adjacency::Manhattan if gap.x.abs() >= gap.y.abs() => Vector::new(gap.x.signum(), 0),
+            Adjacency::Manhattan => Vector::new(0, gap.y.signum()),
+        }
+    }
+
+    /// Every cell a follower passes through catching up with its leader, ending where it stops,
+    /// and empty if it need not move. Under the puzzle's rule that is never more than one step,
+    /// but a leader moving diagonally can get two steps ahead under Manhattan adjacency.
+    pub fn path(&self, leader: Vector, follower: Vector) -> impl Iterator<Item = Vector> {
+        let rule = *self;
+        std::iter::successors(Some(follower), move |&follower| {
+            let gap = leader - follower;
+            (rule.distance(gap) > rule.slack.max(0)).then(|| follower + rule.step(gap))
+        })
+        .skip(1)
+    }
+
+    /// Where a follower ends up once it has caught up with its leader
+    pub fn follow(&self, leader: Vector, follower: Vector) -> Vector {
+        self.path(leader, follower).last().unwrap_or(follower)
+    }
+}
+
+impl Default for FollowRule {
+    fn default() -> Self {
+        Self::PUZZLE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follow() {
+        let puzzle = FollowRule::PUZZLE;
+        assert_eq!(
+            puzzle.follow(Vector::new(1, 1), Vector::new(0, 0)),
+            Vector::new(0, 0)
+        );
+        assert_eq!(
+            puzzle.follow(Vector::new(2, 1), Vector::new(0, 0)),
+            Vector::new(1, 1)
+        );
+
+        let manhattan = FollowRule::new(Adjacency::Manhattan, 1);
+        assert_eq!(
+            manhattan.follow(Vector::new(1, 1), Vector::new(0, 0)),
+            Vector::new(1, 0)
+        );
+        assert_eq!(
+            manhattan.follow(Vector::new(2, 2), Vector::new(0, 0)),
+            Vector::new(2, 1)
+        );
+
+        assert_eq!(
+            manhattan
+                .path(Vector::new(2, 2), Vector::new(0, 0))
+                .collect::<Vec<_>>(),
+            vec![Vector::new(1, 0), Vector::new(1, 1), Vector::new(2, 1)]
+        );
+        assert_eq!(
+            manhattan.path(Vector::new(1, 0), Vector::new(0, 0)).count(),
+            0
+        );
+
+        let loose = FollowRule::new(Adjacency::Chebyshev, 2);
+        assert_eq!(
+            loose.follow(Vector::new(2, 1), Vector::new(0, 0)),
+            Vector::new(0, 0)
+        );
+        assert_eq!(
+            loose.follow(Vector::new(3, 1), Vector::new(0, 0)),
+            Vector::new(1, 1)
+        );
+    }
+}
@@ -1,247 +1,214 @@
+pub mod follow;
 pub mod parser;
-use std::{
-    collections::HashSet,
-    ops::{Add, AddAssign, SubAssign},
-};
-
-#[derive(Copy, Debug, Clone)]
-pub struct Vector {
-    x: i32,
-    y: i32,
-}
-
-impl SubAssign<Vector> for Vector {
-    fn sub_assign(&mut self, rhs: Vector) {
-        self.x = self.x - rhs.x;
-        self.y = self.y - rhs.y;
-    }
+pub mod render;
+pub mod vector;
+pub mod visits;
+
+use follow::FollowRule;
+pub use vector::Vector;
+use visits::Visits;
+
+/// A rope made of knots, where the head is moved directly and every other knot follows the one
+/// in front of it
+#[derive(Debug, Clone)]
+pub struct Rope {
+    // stretching from head to tail
+    knots: Vec<Vector>,
+    tracked: usize,
+    visited: Visits,
+    rule: FollowRule,
 }
 
-impl Vector {
-    fn distance_between(a: &(i32, i32), b: &(i32, i32)) -> Self {
+impl Rope {
+    /// A rope of `length` knots all starting at the origin, recording where its tail goes
+    pub fn new(length: usize) -> Self {
+        assert!(length > 0, "a rope needs at least one knot");
         Self {
-            x: a.0 - b.0,
-            y: a.1 - b.1,
+            knots: vec![Vector::ZERO; length],
+            tracked: length - 1,
+            visited: Visits::new(),
+            rule: FollowRule::PUZZLE,
         }
+        .tracking(length - 1)
     }
-    fn points_to_adjacent(&self) -> bool {
-        matches!(
-            &self,
-            Vector {
-                x: -1..=1,
-                y: -1..=1
-            }
-        )
-    }
-}
 
-trait Normalizable {
-    fn normalize(self) -> Self;
-}
-
-impl Normalizable for Vector {
-    fn normalize(self) -> Self {
-        match self {
-            Vector { x, y } if x < 0 && y < 0 => Vector { x: -1, y: -1 },
-            Vector { x, y } if x > 0 && y < 0 => Vector { x: 1, y: -1 },
-            Vector { x, y } if x < 0 && y > 0 => Vector { x: -1, y: 1 },
-            Vector { x, y } if x > 0 && y > 0 => Vector { x: 1, y: 1 },
-            Vector { x, y: 0 } if x < 0 => Vector { x: -1, y: 0 },
-            Vector { x, y: 0 } if x > 0 => Vector { x: 1, y: 0 },
-            Vector { x: 0, y } if y < 0 => Vector { x: 0, y: -1 },
-            Vector { x: 0, y } if y > 0 => Vector { x: 0, y: 1 },
-            _ => unreachable!(),
-        }
+    /// Have each knot follow the one in front of it by `rule` instead of the puzzle's rule
+    pub fn with_rule(mut self, rule: FollowRule) -> Self {
+        self.rule = rule;
+        self
     }
-}
 
-impl Normalizable for (i32, i32) {
-    fn normalize(self) -> Self {
-        match self {
-            (x, y) if x < 0 && y < 0 => (-1, -1),
-            (x, y) if x > 0 && y < 0 => (1, -1),
-            (x, y) if x < 0 && y > 0 => (-1, 1),
-            (x, y) if x > 0 && y > 0 => (1, 1),
-            (x, 0) if x < 0 => (-1, 0),
-            (x, 0) if x > 0 => (1, 0),
-            (0, y) if y < 0 => (0, -1),
-            (0, y) if y > 0 => (0, 1),
-            _ => unreachable!(),
-        }
+    /// Record where the knot at `knot` goes instead, counting from 0 at the head. Forgets
+    /// anything recorded so far.
+    pub fn tracking(mut self, knot: usize) -> Self {
+        assert!(
+            knot < self.knots.len(),
+            "knot {knot} is not on a rope of {} knots",
+            self.knots.len()
+        );
+        self.tracked = knot;
+        self.visited = Visits::new();
+        self.visited.record(self.knots[knot]);
+        self
     }
-}
 
-impl Vector {
-    fn reverse(&self) -> Self {
-        Self {
-            x: -self.x,
-            y: -self.y,
-        }
+    /// Every knot's position, from the head to the tail
+    pub fn knots(&self) -> &[Vector] {
+        &self.knots
     }
-}
-
-impl Add<Vector> for (i32, i32) {
-    type Output = Self;
 
-    fn add(self, rhs: Vector) -> Self::Output {
-        let (x, y) = self;
-        (x + rhs.x, y + rhs.y)
+    pub fn head(&self) -> Vector {
+        self.knots[0]
     }
-}
 
-impl AddAssign<Vector> for (i32, i32) {
-    fn add_assign(&mut self, rhs: Vector) {
-        self.0 += rhs.x;
-        self.1 += rhs.y;
+    pub fn tail(&self) -> Vector {
+        self.knots[self.knots.len() - 1]
     }
-}
 
-#[derive(Debug)]
-pub struct LongTail {
-    // stretching from head to tail
-    positions: [(i32, i32); 10],
-}
-
-impl LongTail {
-    pub fn new() -> Self {
-        Self {
-            positions: [(0, 0); 10],
-        }
+    /// Every position the tracked knot has been at, including where it started, counting each
+    /// time it moved there
+    pub fn visited(&self) -> &Visits {
+        &self.visited
     }
 
-    pub fn apply_vector_and_record_steps(
-        &mut self,
-        mut vector: Vector,
-        record: &mut HashSet<(i32, i32)>,
-    ) {
-        while !matches!(vector, Vector { x: 0, y: 0 }) {
-            let step = vector.normalize();
+    /// Move the head along `vector` one step at a time, letting the rest of the rope catch up
+    /// after each step
+    pub fn apply(&mut self, mut vector: Vector) {
+        while let Some(step) = vector.normalize() {
             vector -= step;
-            self.positions[0] += step;
-            for (i, next_i) in (0..self.positions.len()).zip(1..self.positions.len()) {
-                let (leader, follower) =
-                    (self.positions[i], self.positions.get_mut(next_i).unwrap());
-                let difference = Vector::distance_between(&leader, follower);
-                if difference.points_to_adjacent() {
-                    continue;
-                }
-                let step = difference.normalize();
-                *follower += step;
-            }
-            record.insert(*self.positions.last().unwrap());
+            self.step(step);
         }
     }
-}
-
-impl Default for LongTail {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
-pub struct Tail {
-    position: (i32, i32),
-    relative_head_position: (i32, i32),
-}
-
-impl Tail {
-    pub fn new() -> Self {
-        Tail {
-            position: (0, 0),
-            relative_head_position: (0, 0),
+    /// Move the head by a single step of at most one in each direction, and let the rest of the
+    /// rope catch up by its follow rule. Every cell the tracked knot passes through is recorded.
+    pub fn step(&mut self, step: Vector) {
+        self.knots[0] += step;
+        if self.tracked == 0 && step != Vector::ZERO {
+            self.visited.record(self.knots[0]);
         }
-    }
-
-    pub fn apply_vector_and_record_steps(
-        &mut self,
-        vector: Vector,
-        record: &mut HashSet<(i32, i32)>,
-    ) {
-        self.relative_head_position += vector;
-
-        let mut step_towards_head = || -> bool {
-            if let Some(unit_vector) = match self.relative_head_position {
-                (-1..=1, -1..=1) => None,
-                (i32::MIN..=-2, 0) => Some(Vector { x: -1, y: 0 }),
-                (2..=i32::MAX, 0) => Some(Vector { x: 1, y: 0 }),
-                (0, i32::MIN..=-2) => Some(Vector { x: 0, y: -1 }),
-                (0, 2..=i32::MAX) => Some(Vector { x: 0, y: 1 }),
-                (x, y) if x < 0 && y < 0 => Some(Vector { x: -1, y: -1 }), // SW
-                (x, y) if x < 0 && y > 0 => Some(Vector { x: -1, y: 1 }),  // NW
-                (x, y) if x > 0 && y > 0 => Some(Vector { x: 1, y: 1 }),   // NE
-                (x, y) if x > 0 && y < 0 => Some(Vector { x: 1, y: -1 }),  // SE
-                _ => unreachable!(),
-            } {
-                self.position += unit_vector;
-                self.relative_head_position += unit_vector.reverse();
-                record.insert(self.position);
-            }
-            matches!(self.relative_head_position, (-1..=1, -1..=1))
-        };
-
-        loop {
-            let should_stop = step_towards_head();
-            if should_stop {
-                break;
+        for next in 1..self.knots.len() {
+            for cell in self.rule.path(self.knots[next - 1], self.knots[next]) {
+                self.knots[next] = cell;
+                if next == self.tracked {
+                    self.visited.record(cell);
+                }
             }
         }
     }
 }
 
-impl Default for Tail {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     #[test]
     fn tail_moves() {
-        let mut tail = Tail::new();
-        let mut set: HashSet<(i32, i32)> = HashSet::new();
-        set.insert((0, 0));
-        tail.apply_vector_and_record_steps(Vector { x: 4, y: 0 }, &mut set);
+        let mut rope = Rope::new(2);
+        rope.apply(Vector { x: 4, y: 0 });
+        let set = rope.visited();
         assert_eq!(set.len(), 4);
-        assert!(set.contains(&(0, 0)));
-        assert!(set.contains(&(1, 0)));
-        assert!(set.contains(&(2, 0)));
-        assert!(set.contains(&(3, 0)));
-        tail.apply_vector_and_record_steps(Vector { x: 0, y: 3 }, &mut set);
-        assert!(set.contains(&(4, 1)));
-        assert!(set.contains(&(4, 2)));
+        assert!(set.contains(Vector::new(0, 0)));
+        assert!(set.contains(Vector::new(1, 0)));
+        assert!(set.contains(Vector::new(2, 0)));
+        assert!(set.contains(Vector::new(3, 0)));
+        rope.apply(Vector { x: 0, y: 3 });
+        let set = rope.visited();
+        assert!(set.contains(Vector::new(4, 1)));
+        assert!(set.contains(Vector::new(4, 2)));
         assert_eq!(set.len(), 6);
-        tail.apply_vector_and_record_steps(Vector { x: -2, y: 0 }, &mut set);
-        assert!(set.contains(&(3, 3)));
+        rope.apply(Vector { x: -2, y: 0 });
+        let set = rope.visited();
+        assert!(set.contains(Vector::new(3, 3)));
         assert_eq!(set.len(), 7);
-        tail.apply_vector_and_record_steps(Vector { x: 0, y: -5 }, &mut set);
-        assert!(set.contains(&(2, 2)));
-        assert!(set.contains(&(2, 1)));
-        assert!(set.contains(&(2, 0))); // already exists
-        assert!(set.contains(&(2, -1)));
+        rope.apply(Vector { x: 0, y: -5 });
+        let set = rope.visited();
+        assert!(set.contains(Vector::new(2, 2)));
+        assert!(set.contains(Vector::new(2, 1)));
+        assert!(set.contains(Vector::new(2, 0))); // already exists
+        assert!(set.contains(Vector::new(2, -1)));
         assert_eq!(set.len(), 10);
+        assert_eq!(rope.head(), Vector::new(2, -2));
+        assert_eq!(rope.tail(), Vector::new(2, -1));
     }
 
-    #[test]
-    fn long_tail_test_input() {
-        let (_, vectors) = parser::parse(
-            "R 5
+    const LARGER_EXAMPLE: &str = "R 5
 U 8
 L 8
 D 3
 R 17
 D 10
 L 25
-U 20",
-        )
-        .expect("input must parse");
-        let mut tail = LongTail::new();
-        let mut record = HashSet::new();
-        record.insert((0, 0));
+U 20";
+
+    #[test]
+    fn long_tail_test_input() {
+        let (_, vectors) = parser::parse(LARGER_EXAMPLE).expect("input must parse");
+        let mut rope = Rope::new(10);
         for vector in vectors {
-            tail.apply_vector_and_record_steps(vector, &mut record);
+            rope.apply(vector);
         }
-        assert_eq!(record.len(), 36);
+        assert_eq!(rope.visited().len(), 36);
+    }
+
+    #[test]
+    fn tracked_knot() {
+        let (_, vectors) = parser::parse(LARGER_EXAMPLE).expect("input must parse");
+        // the knot behind the head moves the same way however much rope trails behind it
+        let mut long = Rope::new(10).tracking(1);
+        let mut short = Rope::new(2);
+        for vector in vectors {
+            long.apply(vector);
+            short.apply(vector);
+        }
+        assert_eq!(long.visited(), short.visited());
+        assert_eq!(long.knots()[1], short.tail());
+    }
+
+    #[test]
+    fn follow_rules() {
+        use follow::{Adjacency, FollowRule};
+
+        let (_, vectors) = parser::parse("UR 3\nR 2").expect("input must parse");
+        let mut puzzle = Rope::new(3);
+        let mut manhattan = Rope::new(3).with_rule(FollowRule::new(Adjacency::Manhattan, 1));
+        let mut loose = Rope::new(3).with_rule(FollowRule::new(Adjacency::Chebyshev, 2));
+        for vector in vectors {
+            puzzle.apply(vector);
+            manhattan.apply(vector);
+            loose.apply(vector);
+        }
+        assert_eq!(
+            puzzle.knots(),
+            &[Vector::new(5, 3), Vector::new(4, 3), Vector::new(3, 3)]
+        );
+        // knots can't move diagonally, so they trail along the bottom of the head's path
+        assert_eq!(
+            manhattan.knots(),
+            &[Vector::new(5, 3), Vector::new(5, 2), Vector::new(5, 1)]
+        );
+        assert_eq!(
+            loose.knots(),
+            &[Vector::new(5, 3), Vector::new(3, 3), Vector::new(1, 1)]
+        );
+        assert_eq!(puzzle.visited().len(), 4);
+        // every cell the tail crosses catching up is visited, not just where it ends up
+        assert_eq!(manhattan.visited().len(), 7);
+        for cell in [(1, 0), (2, 0), (2, 1), (3, 1)] {
+            assert!(manhattan.visited().contains(cell.into()));
+        }
+    }
+
+    #[test]
+    fn visit_counts() {
+        let mut rope = Rope::new(2);
+        rope.apply(Vector::new(4, 0));
+        rope.apply(Vector::new(-4, 0));
+        let visits = rope.visited();
+        let counts = (0..4)
+            .map(|x| visits.count(Vector::new(x, 0)))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![1, 2, 2, 1]);
+        assert_eq!(visits.len(), 4);
+        assert_eq!(visits.total(), 6);
     }
 }
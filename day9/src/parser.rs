@@ -2,7 +2,7 @@ use nom::{
     self,
     bytes::complete::tag,
     character::complete::{digit1, newline, one_of},
-    combinator::{all_consuming, map_res},
+    combinator::{all_consuming, map, map_res, opt},
     multi::separated_list1,
     sequence::separated_pair,
     IResult,
@@ -10,28 +10,53 @@ use nom::{
 
 use crate::Vector;
 
+/// A direction as a unit step: one of `U`, `D`, `L` or `R`, or a diagonal such as `UR`
+fn direction(input: &str) -> IResult<&str, Vector> {
+    let (input, vertical) = opt(one_of("UD"))(input)?;
+    let vertical = match vertical {
+        Some('U') => 1,
+        Some('D') => -1,
+        _ => 0,
+    };
+    let (input, horizontal) = if vertical == 0 {
+        map(one_of("LR"), Some)(input)?
+    } else {
+        opt(one_of("LR"))(input)?
+    };
+    let horizontal = match horizontal {
+        Some('R') => 1,
+        Some('L') => -1,
+        _ => 0,
+    };
+    Ok((input, Vector::new(horizontal, vertical)))
+}
+
 fn line(input: &str) -> IResult<&str, Vector> {
     let (input, (direction, magnitude)) = separated_pair(
-        one_of("UDLR"),
+        direction,
         tag(" "),
         map_res(digit1, |s: &str| s.parse::<i32>()),
     )(input)?;
-    let vector = match direction {
-        'U' => Vector { x: 0, y: magnitude },
-        'D' => Vector {
-            x: 0,
-            y: -magnitude,
-        },
-        'R' => Vector { x: magnitude, y: 0 },
-        'L' => Vector {
-            x: -magnitude,
-            y: 0,
-        },
-        _ => unreachable!(),
-    };
-    Ok((input, vector))
+    Ok((input, direction * magnitude))
 }
 
 pub fn parse(input: &str) -> IResult<&str, Vec<Vector>> {
     all_consuming(separated_list1(newline, line))(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagonals() {
+        let (_, vectors) = parse("U 2\nUR 3\nDL 1\nL 4").expect("input must parse");
+        let vectors = vectors
+            .into_iter()
+            .map(<(i32, i32)>::from)
+            .collect::<Vec<_>>();
+        assert_eq!(vectors, vec![(0, 2), (3, 3), (-1, -1), (-4, 0)]);
+        assert!(parse("RU 3").is_err());
+        assert!(parse("X 3").is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use std::{
+    collections::HashSet,
+    fmt::{Display, Write},
+    fs, io,
+    path::Path,
+};
+
+use crate::{Rope, Vector};
+
+/// The rectangle of positions a diagram covers, corners included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Vector,
+    pub max: Vector,
+}
+
+impl Bounds {
+    pub fn new(min: impl Into<Vector>, max: impl Into<Vector>) -> Self {
+        Self {
+            min: min.into(),
+            max: max.into(),
+        }
+    }
+
+    /// The smallest bounds covering every position given, and the origin
+    pub fn around<'a>(positions: impl IntoIterator<Item = &'a Vector>) -> Self {
+        positions.into_iter().fold(
+            Self::new(Vector::ZERO, Vector::ZERO),
+            |bounds, &position| {
+                Self::new(
+                    (bounds.min.x.min(position.x), bounds.min.y.min(position.y)),
+                    (bounds.max.x.max(position.x), bounds.max.y.max(position.y)),
+                )
+            },
+        )
+    }
+}
+
+impl Rope {
+    /// The rope drawn as in the puzzle: `H` for the head, then each knot's number (or `T` for
+    /// the tail of a two-knot rope), `s` for the start and `.` everywhere else. Up is towards
+    /// the top. Where things overlap the one nearest the head is drawn.
+    pub fn diagram(&self, bounds: Bounds) -> Diagram<'_> {
+        Diagram {
+            rope: self,
+            bounds,
+            show_knots: true,
+        }
+    }
+
+    /// Every position the tracked knot has visited drawn as `#`, with the start as `s`
+    pub fn visited_diagram(&self, bounds: Bounds) -> Diagram<'_> {
+        Diagram {
+            rope: self,
+            bounds,
+            show_knots: false,
+        }
+    }
+
+    fn label(&self, knot: usize) -> char {
+        match knot {
+            0 => 'H',
+            1 if self.knots.len() == 2 => 'T',
+            _ => char::from_digit(knot as u32, 10).unwrap_or('+'),
+        }
+    }
+}
+
+pub struct Diagram<'a> {
+    rope: &'a Rope,
+    bounds: Bounds,
+    show_knots: bool,
+}
+
+impl Display for Diagram<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Bounds { min, max } = self.bounds;
+        for y in (min.y..=max.y).rev() {
+            if y < max.y {
+                writeln!(f)?;
+            }
+            for x in min.x..=max.x {
+                let position = Vector::new(x, y);
+                let knot = self
+                    .show_knots
+                    .then(|| self.rope.knots.iter().position(|&knot| knot == position))
+                    .flatten();
+                let c = match knot {
+                    Some(knot) => self.rope.label(knot),
+                    None if position.is_zero() => 's',
+                    None if !self.show_knots && self.rope.visited.contains(position) => '#',
+                    None => '.',
+                };
+                f.write_char(c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How often an animation takes a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    /// After every single step of the head
+    Step,
+    /// After each whole instruction
+    Instruction,
+}
+
+/// A rope's motion as a series of text frames, each headed by the instruction it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Animation {
+    pub frames: Vec<(String, String)>,
+}
+
+impl Animation {
+    /// Play `vectors` on a rope of `length` knots, starting with a frame of the initial state.
+    /// Every frame is drawn with the same bounds, fitted to everywhere the rope goes unless
+    /// `bounds` are given.
+    pub fn new(
+        length: usize,
+        vectors: &[Vector],
+        granularity: Granularity,
+        bounds: Option<Bounds>,
+    ) -> Self {
+        let mut rope = Rope::new(length);
+        let mut states = vec![("Initial State".to_string(), rope.clone())];
+        for &vector in vectors {
+            let mut remaining = vector;
+            while let Some(step) = remaining.normalize() {
+                remaining -= step;
+                rope.step(step);
+                if granularity == Granularity::Step {
+                    states.push((vector.as_instruction().to_string(), rope.clone()));
+                }
+            }
+            if granularity == Granularity::Instruction {
+                states.push((vector.as_instruction().to_string(), rope.clone()));
+            }
+        }
+
+        let bounds = bounds.unwrap_or_else(|| {
+            let everywhere = states
+                .iter()
+                .flat_map(|(_, rope)| rope.knots.iter().copied())
+                .collect::<HashSet<_>>();
+            Bounds::around(&everywhere)
+        });
+        let frames = states
+            .into_iter()
+            .map(|(label, rope)| (label, rope.diagram(bounds).to_string()))
+            .collect();
+        Self { frames }
+    }
+
+    /// Write each frame to its own numbered file, `frame_0000.txt` onwards, in `dir`
+    pub fn export(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        for (i, frame) in self.frames.iter().enumerate() {
+            fs::write(dir.join(format!("frame_{i:04}.txt")), frame_text(frame))?;
+        }
+        Ok(())
+    }
+}
+
+fn frame_text((label, diagram): &(String, String)) -> String {
+    format!("== {label} ==\n\n{diagram}\n")
+}
+
+/// All the frames one after another, as the puzzle lays out its worked examples
+impl Display for Animation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let frames = self.frames.iter().map(frame_text).collect::<Vec<_>>();
+        write!(f, "{}", frames.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    const EXAMPLE: &str = "R 4
+U 4
+L 3
+D 1
+R 4
+D 1
+L 5
+R 2";
+
+    #[test]
+    fn diagrams() {
+        let (_, vectors) = parser::parse(EXAMPLE).expect("input must parse");
+        let bounds = Bounds::new((0, 0), (5, 4));
+        let mut rope = Rope::new(2);
+        rope.apply(vectors[0]);
+        assert_eq!(
+            rope.diagram(bounds).to_string(),
+            "......\n......\n......\n......\ns..TH."
+        );
+        for &vector in &vectors[1..] {
+            rope.apply(vector);
+        }
+        let expected = "..##..
+...##.
+.####.
+....#.
+s###..";
+        assert_eq!(rope.visited_diagram(bounds).to_string(), expected);
+    }
+
+    #[test]
+    fn animation() {
+        let (_, vectors) = parser::parse(EXAMPLE).expect("input must parse");
+        let bounds = Bounds::new((0, 0), (5, 5));
+        let animation = Animation::new(10, &vectors[..2], Granularity::Instruction, Some(bounds));
+        let expected = "== Initial State ==
+
+......
+......
+......
+......
+......
+H.....
+
+== R 4 ==
+
+......
+......
+......
+......
+......
+4321H.
+
+== U 4 ==
+
+......
+....H.
+....1.
+..432.
+.5....
+6.....
+";
+        assert_eq!(animation.to_string(), expected);
+
+        let animation = Animation::new(2, &vectors, Granularity::Step, None);
+        assert_eq!(animation.frames.len(), 1 + 24);
+        assert_eq!(animation.frames[1].0, "R 4");
+        assert_eq!(
+            animation.frames[1].1,
+            "......\n......\n......\n......\nTH...."
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    fmt::Display,
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+/// A position or displacement on the grid, with `y` increasing upwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Vector {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Vector {
+    pub const ZERO: Self = Self::new(0, 0);
+    pub const UP: Self = Self::new(0, 1);
+    pub const DOWN: Self = Self::new(0, -1);
+    pub const LEFT: Self = Self::new(-1, 0);
+    pub const RIGHT: Self = Self::new(1, 0);
+
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn is_zero(self) -> bool {
+        self == Self::ZERO
+    }
+
+    /// The sign of each component: -1, 0 or 1
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+
+    pub fn abs(self) -> Self {
+        Self::new(self.x.abs(), self.y.abs())
+    }
+
+    /// The single step, diagonal or straight, that heads most directly along this vector.
+    /// `None` for the zero vector, which points nowhere.
+    pub fn normalize(self) -> Option<Self> {
+        (!self.is_zero()).then(|| self.signum())
+    }
+
+    /// Length counting diagonal steps as one, so a king's move away is 1
+    pub fn chebyshev_length(self) -> i32 {
+        self.x.abs().max(self.y.abs())
+    }
+
+    /// Length along rows and columns only, so a diagonal neighbour is 2 away
+    pub fn manhattan_length(self) -> i32 {
+        self.x.abs() + self.y.abs()
+    }
+
+    pub fn chebyshev_distance(self, other: Self) -> i32 {
+        (other - self).chebyshev_length()
+    }
+
+    pub fn manhattan_distance(self, other: Self) -> i32 {
+        (other - self).manhattan_length()
+    }
+
+    /// Displays as the puzzle input would write this vector, such as `R 4`
+    pub fn as_instruction(self) -> AsInstruction {
+        AsInstruction(self)
+    }
+}
+
+impl Display for Vector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// A vector written as a puzzle instruction. Vectors that are neither straight nor diagonal
+/// have no instruction, so they are written as plain coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsInstruction(Vector);
+
+impl Display for AsInstruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.0.x, self.0.y) {
+            (x, 0) if x > 0 => write!(f, "R {x}"),
+            (x, 0) if x < 0 => write!(f, "L {}", -x),
+            (0, y) if y > 0 => write!(f, "U {y}"),
+            (0, y) if y < 0 => write!(f, "D {}", -y),
+            (x, y) if x.abs() == y.abs() && x != 0 => {
+                let vertical = if y > 0 { 'U' } else { 'D' };
+                let horizontal = if x > 0 { 'R' } else { 'L' };
+                write!(f, "{vertical}{horizontal} {}", x.abs())
+            }
+            _ => write!(f, "{}", self.0),
+        }
+    }
+}
+
+impl From<(i32, i32)> for Vector {
+    fn from((x, y): (i32, i32)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<Vector> for (i32, i32) {
+    fn from(vector: Vector) -> Self {
+        (vector.x, vector.y)
+    }
+}
+
+impl Add for Vector {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vector {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign for Vector {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Vector {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i32> for Vector {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl MulAssign<i32> for Vector {
+    fn mul_assign(&mut self, rhs: i32) {
+        *self = *self * rhs;
+    }
+}
+
+impl Sum for Vector {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = Vector::new(3, -2);
+        assert_eq!(a + Vector::UP, Vector::new(3, -1));
+        assert_eq!(a - a, Vector::ZERO);
+        assert_eq!(-a * 2, Vector::new(-6, 4));
+        assert_eq!(
+            [a, Vector::LEFT, Vector::DOWN].into_iter().sum::<Vector>(),
+            Vector::new(2, -3)
+        );
+        assert_eq!(Vector::from((3, -2)), a);
+        assert_eq!(<(i32, i32)>::from(a), (3, -2));
+    }
+
+    #[test]
+    fn lengths() {
+        let a = Vector::new(3, -2);
+        assert_eq!(a.signum(), Vector::new(1, -1));
+        assert_eq!(a.normalize(), Some(Vector::new(1, -1)));
+        assert_eq!(Vector::new(0, 7).normalize(), Some(Vector::UP));
+        assert_eq!(Vector::ZERO.normalize(), None);
+        assert_eq!(a.chebyshev_length(), 3);
+        assert_eq!(a.manhattan_length(), 5);
+        assert_eq!(a.chebyshev_distance(Vector::new(1, 1)), 3);
+        assert_eq!(a.manhattan_distance(Vector::new(1, 1)), 5);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Vector::new(3, 0).to_string(), "(3, 0)");
+        assert_eq!(Vector::new(3, 0).as_instruction().to_string(), "R 3");
+        assert_eq!(Vector::new(-2, -2).as_instruction().to_string(), "DL 2");
+        assert_eq!(Vector::new(3, 1).as_instruction().to_string(), "(3, 1)");
+        assert_eq!(Vector::ZERO.as_instruction().to_string(), "(0, 0)");
+    }
+}
@@ -0,0 +1,259 @@
+use std::{collections::HashMap, fmt::Display, mem::size_of};
+
+use crate::Vector;
+
+/// Cells along each side of a tile
+const TILE_SIDE: i32 = 16;
+const TILE_CELLS: usize = (TILE_SIDE * TILE_SIDE) as usize;
+
+/// The visited cells of one tile: a bit per cell, and a count per visited cell in the order of
+/// the set bits. Counts that reach `u8::MAX` carry on in `Visits::overflow`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Tile {
+    visited: [u64; TILE_CELLS / 64],
+    counts: Vec<u8>,
+}
+
+impl Tile {
+    fn contains(&self, index: usize) -> bool {
+        self.visited[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Where the count for cell `index` goes in `counts`: the number of visited cells before it
+    fn rank(&self, index: usize) -> usize {
+        let (word, bit) = (index / 64, index % 64);
+        let before: u32 = self.visited[..word].iter().map(|w| w.count_ones()).sum();
+        (before + (self.visited[word] & ((1 << bit) - 1)).count_ones()) as usize
+    }
+
+    /// Every visited cell's index, in order
+    fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.visited.iter().enumerate().flat_map(|(word, &bits)| {
+            let mut bits = bits;
+            std::iter::from_fn(move || {
+                (bits != 0).then(|| {
+                    let bit = bits.trailing_zeros() as usize;
+                    bits &= bits - 1;
+                    word * 64 + bit
+                })
+            })
+        })
+    }
+}
+
+/// How many times each cell has been visited, for a grid that can grow without bound in every
+/// direction.
+///
+/// Cells are grouped into square tiles that are only allocated once something visits them. Each
+/// tile costs about 70 bytes for its bitmap and map entry, plus a byte per visited cell, so even
+/// a long thin path that only visits 16 cells of each tile stays under 10 bytes per cell. Cells
+/// visited more than 254 times also keep their count in a separate map.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Visits {
+    tiles: HashMap<Vector, Tile>,
+    overflow: HashMap<Vector, u32>,
+    distinct: usize,
+    total: u64,
+}
+
+impl Visits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The tile holding `position`, and the position's index within it
+    fn locate(position: Vector) -> (Vector, usize) {
+        let tile = Vector::new(
+            position.x.div_euclid(TILE_SIDE),
+            position.y.div_euclid(TILE_SIDE),
+        );
+        let x = position.x.rem_euclid(TILE_SIDE);
+        let y = position.y.rem_euclid(TILE_SIDE);
+        (tile, (y * TILE_SIDE + x) as usize)
+    }
+
+    /// Count one more visit to `position`
+    pub fn record(&mut self, position: Vector) {
+        let (tile, index) = Self::locate(position);
+        let tile = self.tiles.entry(tile).or_default();
+        let rank = tile.rank(index);
+        if !tile.contains(index) {
+            tile.visited[index / 64] |= 1 << (index % 64);
+            tile.counts.insert(rank, 1);
+            self.distinct += 1;
+        } else if tile.counts[rank] < u8::MAX {
+            tile.counts[rank] += 1;
+        } else {
+            let count = self.overflow.entry(position).or_insert(u8::MAX.into());
+            *count = count.saturating_add(1);
+        }
+        self.total += 1;
+    }
+
+    /// How many times `position` has been visited
+    pub fn count(&self, position: Vector) -> u32 {
+        let (tile, index) = Self::locate(position);
+        match self.tiles.get(&tile) {
+            Some(tile) if tile.contains(index) => {
+                self.stored_count(position, tile.counts[tile.rank(index)])
+            }
+            _ => 0,
+        }
+    }
+
+    /// The full count behind a tile's `count`, which stops at `u8::MAX`
+    fn stored_count(&self, position: Vector, count: u8) -> u32 {
+        if count == u8::MAX {
+            self.overflow[&position]
+        } else {
+            count.into()
+        }
+    }
+
+    pub fn contains(&self, position: Vector) -> bool {
+        self.count(position) > 0
+    }
+
+    /// How many different cells have been visited
+    pub fn len(&self) -> usize {
+        self.distinct
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.distinct == 0
+    }
+
+    /// How many visits have been recorded in all
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Every visited cell with its visit count, tile by tile
+    pub fn iter(&self) -> impl Iterator<Item = (Vector, u32)> + '_ {
+        self.tiles.iter().flat_map(move |(&origin, tile)| {
+            tile.indices()
+                .zip(&tile.counts)
+                .map(move |(index, &count)| {
+                    let (x, y) = (index as i32 % TILE_SIDE, index as i32 / TILE_SIDE);
+                    let position = origin * TILE_SIDE + Vector::new(x, y);
+                    (position, self.stored_count(position, count))
+                })
+        })
+    }
+
+    /// How much memory the record takes up
+    pub fn footprint(&self) -> Footprint {
+        // each map stores a key, a value and a control byte per slot, and each tile's counts live
+        // in their own allocation
+        let tile_slot = size_of::<Vector>() + size_of::<Tile>() + 1;
+        let overflow_slot = size_of::<Vector>() + size_of::<u32>() + 1;
+        Footprint {
+            tiles: self.tiles.len(),
+            cells: self.distinct,
+            bytes: size_of::<Self>()
+                + self.tiles.capacity() * tile_slot
+                + self
+                    .tiles
+                    .values()
+                    .map(|tile| tile.counts.capacity())
+                    .sum::<usize>()
+                + self.overflow.capacity() * overflow_slot,
+        }
+    }
+}
+
+/// A summary of the memory a `Visits` uses. Bytes are an estimate, since they depend on how the
+/// hash map lays out its table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Footprint {
+    pub tiles: usize,
+    /// Visited cells
+    pub cells: usize,
+    pub bytes: usize,
+}
+
+impl Display for Footprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} cells visited in {} tiles of {}x{}: about {} bytes",
+            self.cells, self.tiles, TILE_SIDE, TILE_SIDE, self.bytes
+        )?;
+        if self.cells > 0 {
+            write!(
+                f,
+                " ({:.1} per cell)",
+                self.bytes as f64 / self.cells as f64
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn counts() {
+        let mut visits = Visits::new();
+        assert!(visits.is_empty());
+        // either side of the tile boundaries around the origin
+        for position in [(0, 0), (-1, -1), (15, 16), (-1, -1), (-17, 3)] {
+            visits.record(position.into());
+        }
+        assert_eq!(visits.len(), 4);
+        assert_eq!(visits.total(), 5);
+        assert_eq!(visits.count(Vector::new(-1, -1)), 2);
+        assert_eq!(visits.count(Vector::new(15, 16)), 1);
+        assert!(!visits.contains(Vector::new(16, 15)));
+
+        let mut cells = visits.iter().collect::<Vec<_>>();
+        cells.sort();
+        assert_eq!(
+            cells,
+            vec![
+                (Vector::new(-17, 3), 1),
+                (Vector::new(-1, -1), 2),
+                (Vector::new(0, 0), 1),
+                (Vector::new(15, 16), 1),
+            ]
+        );
+
+        let footprint = visits.footprint();
+        assert_eq!(footprint.tiles, 4);
+        assert!(footprint.bytes >= 4 * size_of::<Tile>());
+
+        // counts carry on past what a tile stores
+        for _ in 0..300 {
+            visits.record(Vector::new(15, 16));
+        }
+        assert_eq!(visits.count(Vector::new(15, 16)), 301);
+        assert!(visits.iter().any(|cell| cell == (Vector::new(15, 16), 301)));
+        assert_eq!(visits.len(), 4);
+    }
+
+    #[test]
+    fn thin_paths_stay_compact() {
+        // a long straight walk and a long diagonal one only use 16 cells of each tile they cross
+        for step in [Vector::RIGHT, Vector::new(1, 1)] {
+            let mut visits = Visits::new();
+            let mut set = HashSet::new();
+            let mut position = Vector::ZERO;
+            for _ in 0..100_000 {
+                visits.record(position);
+                set.insert((position.x, position.y));
+                position += step;
+            }
+            let footprint = visits.footprint();
+            let set_bytes = set.capacity() * (size_of::<(i32, i32)>() + 1);
+            assert_eq!(footprint.cells, set.len());
+            assert!(
+                footprint.bytes < set_bytes,
+                "{footprint} against {set_bytes} bytes for a HashSet"
+            );
+        }
+    }
+}